@@ -29,6 +29,7 @@ fn main() {
                                     )
                                 }))
                                 .background(Color::BLACK)
+                                .hover_background(Color::DARK_GRAY)
                                 .on_click(|| {
                                     let count = COUNTER.get();
                                     count.set(count.get() + 1);
@@ -0,0 +1,35 @@
+#![cfg_attr(
+    all(not(debug_assertions), target_os = "windows"),
+    windows_subsystem = "windows"
+)]
+
+use skia_safe::Color;
+use yalem::{widgets::*, App, Window};
+
+fn main() {
+    yalem::run(
+        App::new().with_window(
+            Window::new()
+                .with_title("yalem Demo")
+                .root(LayoutBuilder::new(|ctx| {
+                    if ctx.width() < 400.0 {
+                        Box::new(
+                            Text::builder("Compact layout")
+                                .color(Color::BLACK)
+                                .build(),
+                        )
+                    } else {
+                        Box::new(
+                            Padding::builder((50.0, 50.0, 25.0, 25.0))
+                                .child(
+                                    Text::builder("Wide layout")
+                                        .color(Color::BLUE)
+                                        .build(),
+                                )
+                                .build(),
+                        )
+                    }
+                })),
+        ),
+    )
+}
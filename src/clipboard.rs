@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+
+use arboard::Clipboard;
+
+thread_local! {
+    // Opened on first use, some platforms fail when there is no display
+    static CLIPBOARD: RefCell<Option<Clipboard>> = const { RefCell::new(None) };
+
+    // Stands in for the system clipboard in tests, which may run without a display
+    static TEST_CLIPBOARD: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn with_clipboard<T>(f: impl FnOnce(&mut Clipboard) -> Option<T>) -> Option<T> {
+    CLIPBOARD.with(|clipboard| {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Clipboard::new().ok();
+        }
+        clipboard.as_mut().and_then(f)
+    })
+}
+
+/// Text currently in the system clipboard, if any.
+pub(crate) fn get_text() -> Option<String> {
+    if cfg!(test) {
+        return TEST_CLIPBOARD.with(|text| text.borrow().clone());
+    }
+
+    with_clipboard(|clipboard| clipboard.get_text().ok())
+}
+
+pub(crate) fn set_text(text: &str) {
+    if cfg!(test) {
+        TEST_CLIPBOARD.with(|test_text| *test_text.borrow_mut() = Some(text.to_string()));
+        return;
+    }
+
+    with_clipboard(|clipboard| {
+        clipboard
+            .set_text(text.to_string())
+            .ok()
+    });
+}
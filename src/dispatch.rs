@@ -0,0 +1,200 @@
+use std::cell::{Cell, RefCell};
+
+use crate::{rect_contains, set_focus, FocusId, PointerId, Widget, YalemEvent};
+
+thread_local! {
+    // Hit path of the widget an event is being sent to
+    static TARGET: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+
+    // Pointers captured, with the widget capturing them, or released while handling
+    // the event
+    static CAPTURES: RefCell<Vec<(PointerId, Option<Vec<usize>>)>> = const { RefCell::new(vec![]) };
+
+    // Whether a widget took the focus with `take_focus` while handling the event
+    static FOCUS_TAKEN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Have the widget the current event is sent to capture `pointer`, or release it.
+pub(crate) fn request_capture(pointer: PointerId, capture: bool) {
+    let path = capture.then(|| TARGET.with(|target| target.borrow().clone()));
+    CAPTURES.with(|captures| {
+        captures
+            .borrow_mut()
+            .push((pointer, path))
+    });
+}
+
+pub(crate) fn take_captures() -> Vec<(PointerId, Option<Vec<usize>>)> {
+    CAPTURES.with(|captures| captures.take())
+}
+
+/// Focus `id` unless a widget the current event bubbled up from already took the focus,
+/// so the innermost of nested widgets gets it.
+pub(crate) fn take_focus(id: FocusId) {
+    if !FOCUS_TAKEN.with(|taken| taken.replace(true)) {
+        set_focus(Some(id));
+    }
+}
+
+/// Run `send` with the widget at `path` as the target, for `request_capture`.
+fn with_target<T>(path: &[usize], send: impl FnOnce() -> T) -> T {
+    TARGET.with(|target| *target.borrow_mut() = path.to_vec());
+    send()
+}
+
+/// Find the deepest widget under `position`, pushing the index of each child on the way
+/// to it into `path`. Widgets painted last are on top, so they are tried first.
+pub(crate) fn hit_test(
+    widget: &mut dyn Widget,
+    position: (f64, f64),
+    path: &mut Vec<usize>,
+) -> bool {
+    // Nothing in a subtree is drawn outside of its area
+    if let Some(area) = widget.area() {
+        if !rect_contains(&area, position) {
+            return false;
+        }
+    }
+
+    for (index, child) in widget
+        .children_mut()
+        .into_iter()
+        .enumerate()
+        .rev()
+    {
+        path.push(index);
+        if hit_test(child, position, path) {
+            return true;
+        }
+        path.pop();
+    }
+
+    widget.hit_test(position)
+}
+
+/// Capture `event` down to the widget at the end of `path`, then bubble it back up to
+/// the root, until a widget handles it. Returns whether one did.
+pub(crate) fn dispatch(widget: &mut dyn Widget, path: &[usize], event: &YalemEvent) -> bool {
+    FOCUS_TAKEN.with(|taken| taken.set(false));
+    dispatch_from(widget, path, 0, event)
+}
+
+/// `dispatch` to the widget at `path[..depth]`.
+fn dispatch_from(
+    widget: &mut dyn Widget,
+    path: &[usize],
+    depth: usize,
+    event: &YalemEvent,
+) -> bool {
+    let own_path = &path[..depth];
+    if with_target(own_path, || widget.capture_event(event)) {
+        return true;
+    }
+
+    if let Some(index) = path.get(depth) {
+        if let Some(child) = widget
+            .children_mut()
+            .into_iter()
+            .nth(*index)
+        {
+            if dispatch_from(child, path, depth + 1, event) {
+                return true;
+            }
+        }
+    }
+
+    with_target(own_path, || widget.send_event(event))
+}
+
+/// Send `event` to every widget on `path`, innermost first, whether or not they handle
+/// it. For events every one of them waits for, like the release of a press.
+pub(crate) fn deliver(widget: &mut dyn Widget, path: &[usize], event: &YalemEvent) {
+    deliver_from(widget, path, 0, event);
+}
+
+fn deliver_from(widget: &mut dyn Widget, path: &[usize], depth: usize, event: &YalemEvent) {
+    let own_path = &path[..depth];
+    with_target(own_path, || widget.capture_event(event));
+
+    if let Some(index) = path.get(depth) {
+        if let Some(child) = widget
+            .children_mut()
+            .into_iter()
+            .nth(*index)
+        {
+            deliver_from(child, path, depth + 1, event);
+        }
+    }
+
+    with_target(own_path, || widget.send_event(event));
+}
+
+/// Send `event` to every widget in the tree, children before their parent. It isn't
+/// tied to a position, so handling it doesn't keep it from siblings or ancestors, but
+/// a widget capturing it keeps it from its own descendants.
+pub(crate) fn broadcast(widget: &mut dyn Widget, event: &YalemEvent) {
+    if widget.capture_event(event) {
+        return;
+    }
+
+    for child in widget.children_mut() {
+        broadcast(child, event);
+    }
+
+    widget.send_event(event);
+}
+
+/// Widget at the end of `path`.
+pub(crate) fn find<'a>(widget: &'a mut dyn Widget, path: &[usize]) -> Option<&'a mut dyn Widget> {
+    match path.split_first() {
+        Some((index, rest)) => {
+            let child = widget
+                .children_mut()
+                .into_iter()
+                .nth(*index)?;
+            find(child, rest)
+        }
+        None => Some(widget),
+    }
+}
+
+/// Send `event` to the widget at the end of `path` only.
+pub(crate) fn send_to(widget: &mut dyn Widget, path: &[usize], event: &YalemEvent) {
+    if let Some(widget) = find(widget, path) {
+        with_target(path, || widget.send_event(event));
+    }
+}
+
+/// Like `hit_test`, but finds the widget with the focus id `id`.
+pub(crate) fn focus_path(widget: &mut dyn Widget, id: FocusId, path: &mut Vec<usize>) -> bool {
+    if widget.focus_id() == Some(id) {
+        return true;
+    }
+
+    for (index, child) in widget
+        .children_mut()
+        .into_iter()
+        .enumerate()
+    {
+        path.push(index);
+        if focus_path(child, id, path) {
+            return true;
+        }
+        path.pop();
+    }
+
+    false
+}
+
+/// Focus ids of the widgets Tab goes through, in tree order.
+pub(crate) fn tab_order(widget: &mut dyn Widget, order: &mut Vec<FocusId>) {
+    if let Some(id) = widget.focus_id() {
+        if widget.in_tab_order() {
+            order.push(id);
+        }
+    }
+
+    for child in widget.children_mut() {
+        tab_order(child, order);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    path::PathBuf,
+};
+
+use skia_safe::Canvas;
+
+use crate::{Context, PointerId, Widget};
+
+/// Opacity of the drag feedback, out of 255
+const FEEDBACK_ALPHA: u32 = 0xB0;
+
+thread_local! {
+    // Drag in progress, in the app or from another one
+    static DRAG: RefCell<Option<Drag>> = const { RefCell::new(None) };
+
+    // Whether the drag started, moved or ended since the window last looked
+    static CHANGED: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DragState {
+    Dragging,
+    Dropped,
+    Cancelled,
+}
+
+/// Payload being dragged, and where to.
+pub(crate) struct Drag {
+    pub(crate) payload: Box<dyn Any>,
+    /// Pointer doing the drag, `None` for files dragged from another app, which follow
+    /// the cursor
+    pub(crate) pointer: Option<PointerId>,
+    pub(crate) position: (f64, f64),
+    pub(crate) state: DragState,
+    feedback: Option<Box<dyn Widget>>,
+}
+
+/// Start dragging `payload` from `position`, replacing the drag in progress.
+pub(crate) fn start(
+    payload: Box<dyn Any>,
+    pointer: Option<PointerId>,
+    position: (f64, f64),
+    feedback: Option<Box<dyn Widget>>,
+) {
+    CHANGED.with(|changed| changed.set(true));
+    DRAG.with(|drag| {
+        *drag.borrow_mut() = Some(Drag {
+            payload,
+            pointer,
+            position,
+            state: DragState::Dragging,
+            feedback,
+        })
+    });
+}
+
+fn update(f: impl FnOnce(&mut Drag)) {
+    DRAG.with(|drag| {
+        if let Some(drag) = drag.borrow_mut().as_mut() {
+            f(drag);
+            CHANGED.with(|changed| changed.set(true));
+        }
+    });
+}
+
+/// Whether the drag changed since the last call.
+pub(crate) fn take_changed() -> bool {
+    CHANGED.with(|changed| changed.replace(false))
+}
+
+/// Files dragged from another app, if they're what is being dragged.
+pub(crate) fn files() -> Option<Vec<PathBuf>> {
+    DRAG.with(|drag| {
+        drag.borrow()
+            .as_ref()
+            .filter(|drag| drag.pointer.is_none() && drag.state == DragState::Dragging)?
+            .payload
+            .downcast_ref::<Vec<PathBuf>>()
+            .cloned()
+    })
+}
+
+/// Add `path` to the files dragged from another app, or start dragging it from
+/// `position`. Apps report each file of a drag on its own.
+pub(crate) fn hover_file(path: PathBuf, position: (f64, f64)) {
+    let mut files = files().unwrap_or_default();
+    files.push(path);
+    start(Box::new(files), None, position, None);
+}
+
+pub(crate) fn move_to(position: (f64, f64)) {
+    update(|drag| drag.position = position);
+}
+
+/// Drop the payload at `position`, on the drop target there if it takes it.
+pub(crate) fn drop_at(position: (f64, f64)) {
+    update(|drag| {
+        drag.position = position;
+        drag.state = DragState::Dropped;
+    });
+}
+
+pub(crate) fn cancel() {
+    update(|drag| drag.state = DragState::Cancelled);
+}
+
+/// Pointer of the drag in progress, `Some(None)` for files from another app.
+pub(crate) fn pointer() -> Option<Option<PointerId>> {
+    DRAG.with(|drag| {
+        drag.borrow()
+            .as_ref()
+            .map(|drag| drag.pointer)
+    })
+}
+
+/// Take the drag out while it's sent to the drop targets, so they can't change it
+/// under their feet. Put it back with `restore` unless it's over.
+pub(crate) fn take() -> Option<Drag> {
+    DRAG.with(|drag| drag.borrow_mut().take())
+}
+
+pub(crate) fn restore(dragged: Drag) {
+    DRAG.with(|drag| {
+        let mut drag = drag.borrow_mut();
+        // A new drag started in the meantime wins
+        if drag.is_none() {
+            *drag = Some(dragged);
+        }
+    });
+}
+
+/// Draw the feedback of the drag at the pointer, on top of everything else.
+pub(crate) fn draw_feedback(canvas: &mut Canvas, ctx: Context) {
+    DRAG.with(|drag| {
+        let mut drag = drag.borrow_mut();
+        let drag = match drag.as_mut() {
+            Some(drag) if drag.state == DragState::Dragging => drag,
+            _ => return,
+        };
+
+        if let Some(feedback) = &mut drag.feedback {
+            canvas.save_layer_alpha(None, FEEDBACK_ALPHA);
+            feedback.draw(
+                canvas,
+                Context {
+                    x: drag.position.0 as f32,
+                    y: drag.position.1 as f32,
+                    ..ctx
+                },
+            );
+            canvas.restore();
+        }
+    });
+}
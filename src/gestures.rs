@@ -0,0 +1,101 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use crate::PointerId;
+
+thread_local! {
+    static NEXT_MEMBER_ID: Cell<usize> = const { Cell::new(0) };
+
+    // Recognizer that won the current press of each pointer
+    static WINNERS: RefCell<HashMap<PointerId, usize>> = RefCell::new(HashMap::new());
+}
+
+// The gesture arena settles which recognizer gets a press when several of them could
+// use it, e.g. a button inside a draggable list. The first one to claim it wins, the
+// others give up on it. Claims are made when a gesture is recognized: a drag once the
+// pointer moves far enough, a long press once its delay is over and a tap when the
+// button is released. Releases reach the innermost widgets first, so the innermost
+// tap wins when nothing else did. Each pointer has its own arena, so two fingers can
+// drag two widgets at once, and a two finger gesture claims both.
+
+/// Id of a recognizer in the arena.
+pub(crate) fn new_member_id() -> usize {
+    NEXT_MEMBER_ID.with(|next_id| {
+        let id = next_id.get();
+        next_id.set(id + 1);
+        id
+    })
+}
+
+/// Start over for a new press of `pointer`.
+pub(crate) fn reset_arena(pointer: PointerId) {
+    WINNERS.with(|winners| winners.borrow_mut().remove(&pointer));
+}
+
+/// Try to win the current press of `pointer`, returns whether `member` has it.
+pub(crate) fn claim(pointer: PointerId, member: usize) -> bool {
+    WINNERS.with(|winners| {
+        *winners
+            .borrow_mut()
+            .entry(pointer)
+            .or_insert(member)
+            == member
+    })
+}
+
+/// Whether another recognizer won the current press of `pointer`.
+pub(crate) fn lost(pointer: PointerId, member: usize) -> bool {
+    WINNERS.with(|winners| {
+        winners
+            .borrow()
+            .get(&pointer)
+            .is_some_and(|id| *id != member)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGER: PointerId = PointerId::Touch(1);
+
+    #[test]
+    fn first_claim_wins() {
+        let (tap, drag) = (new_member_id(), new_member_id());
+        reset_arena(PointerId::Mouse);
+
+        assert!(!lost(PointerId::Mouse, tap));
+        assert!(claim(PointerId::Mouse, drag));
+        assert!(claim(PointerId::Mouse, drag));
+        assert!(!claim(PointerId::Mouse, tap));
+        assert!(lost(PointerId::Mouse, tap));
+        assert!(!lost(PointerId::Mouse, drag));
+    }
+
+    #[test]
+    fn reset_starts_a_new_press() {
+        let (tap, drag) = (new_member_id(), new_member_id());
+        reset_arena(PointerId::Mouse);
+        claim(PointerId::Mouse, drag);
+
+        reset_arena(PointerId::Mouse);
+        assert!(!lost(PointerId::Mouse, tap));
+        assert!(claim(PointerId::Mouse, tap));
+    }
+
+    #[test]
+    fn pointers_have_their_own_arena() {
+        let (first, second) = (new_member_id(), new_member_id());
+        reset_arena(PointerId::Mouse);
+        reset_arena(FINGER);
+
+        assert!(claim(PointerId::Mouse, first));
+        assert!(claim(FINGER, second));
+        assert!(lost(FINGER, first));
+
+        reset_arena(FINGER);
+        assert!(lost(PointerId::Mouse, second));
+    }
+}
@@ -1,16 +1,24 @@
 use glutin::{
-    event::{MouseButton, WindowEvent},
+    event::{ModifiersState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent},
     event_loop::EventLoopProxy,
 };
-use skia_safe::Canvas;
-use std::sync::{Arc, Mutex};
+use skia_safe::{utils::text_utils::Align, Canvas, Paint, PaintStyle, Rect, Typeface};
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use gl::types::*;
-use glutin::dpi::PhysicalSize;
+use glutin::dpi::{PhysicalPosition, PhysicalSize};
 use glutin::event::ElementState;
-use glutin::window::WindowId;
+use glutin::window::{CursorIcon, WindowId};
 use glutin::{
-    event::{Event, KeyboardInput, VirtualKeyCode},
+    event::{Event, KeyboardInput, StartCause, VirtualKeyCode},
     event_loop::{ControlFlow, EventLoop},
     window::WindowBuilder,
     GlProfile,
@@ -21,24 +29,281 @@ use skia_safe::{
     ColorType, Surface,
 };
 
+/// Presses closer than this in time and space count as a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const CLICK_SLOP: f64 = 4.0;
+
+const FOCUS_RING_COLOR: Color = Color::BLUE;
+const FOCUS_RING_WIDTH: f32 = 2.0;
+
+mod clipboard;
+mod dispatch;
+mod drag;
+mod gestures;
+mod shortcuts;
+mod text_layout;
 pub mod widgets;
 
+use drag::DragState;
+use shortcuts::Keymap;
+pub use shortcuts::{trigger_action, Binding, KeyChord};
+
+thread_local! {
+    static NEXT_REDRAW: Cell<Option<Instant>> = const { Cell::new(None) };
+
+    // Caret of the focused text widget, set while a window is drawn
+    static IME_CARET: Cell<Option<Rect>> = const { Cell::new(None) };
+
+    static NEXT_FOCUS_ID: Cell<usize> = const { Cell::new(0) };
+
+    // Widget keyboard input goes to
+    static FOCUSED: Cell<Option<FocusId>> = const { Cell::new(None) };
+
+    static QUIT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Close every window and return from `run` once the current event is handled.
+pub fn quit() {
+    QUIT.with(|quit| quit.set(true));
+}
+
+/// Identifies a widget that can take the keyboard focus. Give one to the widget's
+/// builder to move the focus to it later with `request_focus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FocusId(usize);
+
+impl FocusId {
+    pub fn new() -> Self {
+        NEXT_FOCUS_ID.with(|next_id| {
+            let id = next_id.get();
+            next_id.set(id + 1);
+            Self(id)
+        })
+    }
+
+    pub fn is_focused(self) -> bool {
+        focused() == Some(self)
+    }
+}
+
+impl Default for FocusId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keep sending the moves and release of `pointer` to the widget handling the current
+/// event, even once it's outside of the widget or of the window, e.g. during a drag.
+/// Pointers are captured by the widgets they were pressed on until then. The capture
+/// ends when the pointer is released.
+pub fn capture_pointer(pointer: PointerId) {
+    dispatch::request_capture(pointer, true);
+}
+
+/// End the capture of `pointer`, its moves and release go to the widgets under it again.
+pub fn release_pointer(pointer: PointerId) {
+    dispatch::request_capture(pointer, false);
+}
+
+/// Move the keyboard focus to the widget with `id`.
+pub fn request_focus(id: FocusId) {
+    set_focus(Some(id));
+}
+
+pub(crate) fn set_focus(id: Option<FocusId>) {
+    FOCUSED.with(|focused| focused.set(id));
+}
+
+pub(crate) fn focused() -> Option<FocusId> {
+    FOCUSED.with(|focused| focused.get())
+}
+
+/// Redraw every window once `instant` is reached, e.g. to animate something. Widgets
+/// get a `YalemEvent::Wake` then. The earliest request wins when it's called several times.
+pub fn request_redraw_at(instant: Instant) {
+    NEXT_REDRAW.with(|next_redraw| match next_redraw.get() {
+        Some(next) if next <= instant => {}
+        _ => next_redraw.set(Some(instant)),
+    })
+}
+
+/// Called while drawing by the focused text widget, so input methods are enabled
+/// and their candidate window opens next to `caret`.
+pub(crate) fn set_ime_caret(caret: Rect) {
+    IME_CARET.with(|ime_caret| ime_caret.set(Some(caret)));
+}
+
+/// Horizontal direction in which text flows and layouts are laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+/// Horizontal alignment of text. `Start` and `End` are mirrored in right-to-left
+/// layouts, the others aren't.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+    #[default]
+    Start,
+    End,
+}
+
+impl TextAlign {
+    /// Left, center or right alignment in a layout going in `direction`.
+    pub(crate) fn resolve(self, direction: TextDirection) -> Align {
+        match (self, direction) {
+            (Self::Left, _) => Align::Left,
+            (Self::Center, _) => Align::Center,
+            (Self::Right, _) => Align::Right,
+            (Self::Start, TextDirection::Ltr) | (Self::End, TextDirection::Rtl) => Align::Left,
+            (Self::Start, TextDirection::Rtl) | (Self::End, TextDirection::Ltr) => Align::Right,
+        }
+    }
+}
+
+impl From<Align> for TextAlign {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => Self::Left,
+            Align::Center => Self::Center,
+            Align::Right => Self::Right,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Context {
     x: f32,
     y: f32,
     width: f32,
     height: f32,
+    direction: TextDirection,
+}
+
+impl Context {
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn direction(&self) -> TextDirection {
+        self.direction
+    }
+}
+
+/// Whether `position` falls inside `rect`.
+pub(crate) fn rect_contains(rect: &Rect, (x, y): (f64, f64)) -> bool {
+    let (x, y) = (x as f32, y as f32);
+    x >= rect.left && x <= rect.right && y >= rect.top && y <= rect.bottom
 }
 
 pub trait Widget {
-    fn send_event(&mut self, _event: &YalemEvent) {}
+    /// Handle an event on its way back up from the widget under the pointer (the
+    /// bubble phase). Returning `true` marks it as handled, so the ancestors don't
+    /// get it. Children get their events from the window, so this shouldn't forward them.
+    fn send_event(&mut self, _event: &YalemEvent) -> bool {
+        false
+    }
+
+    /// Handle an event on its way down to the widget under the pointer (the capture
+    /// phase), before any of its descendants. Returning `true` stops it there, e.g. to
+    /// intercept the input of the children.
+    fn capture_event(&mut self, _event: &YalemEvent) -> bool {
+        false
+    }
+
+    /// Children, in the order they are painted.
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        vec![]
+    }
+
+    /// Area the widget and its children were last drawn in, if it keeps track of it.
+    /// Pointer events outside of it skip the whole subtree.
+    fn area(&self) -> Option<Rect> {
+        None
+    }
+
+    /// Whether the widget itself, not its children, takes pointer events at `position`.
+    fn hit_test(&self, _position: (f64, f64)) -> bool {
+        false
+    }
+
+    /// Id the widget takes the keyboard focus with, if it can.
+    fn focus_id(&self) -> Option<FocusId> {
+        None
+    }
+
+    /// Whether Tab and Shift+Tab stop on the widget when it can take the focus.
+    fn in_tab_order(&self) -> bool {
+        true
+    }
+
+    /// Where the focus ring is drawn while the widget has the focus, `None` for widgets
+    /// that show it themselves.
+    fn focus_ring(&self) -> Option<Rect> {
+        self.area()
+    }
+
+    /// Mouse cursor over the widget, `None` to leave it to its ancestors.
+    fn cursor(&self) -> Option<CursorIcon> {
+        None
+    }
+
+    /// Whether the widget takes the text typed while it has the focus, so the plain
+    /// keys typing it don't trigger shortcuts.
+    fn accepts_text(&self) -> bool {
+        false
+    }
 
     fn draw(&mut self, canvas: &mut Canvas, context: Context);
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
         (ctx.width, ctx.height)
     }
+
+    // The intrinsic sizes default to the loosest answers, widgets that don't know
+    // better can shrink to nothing and take all the space they are given
+
+    /// Narrowest width the widget can be given without clipping its content.
+    fn min_intrinsic_width(&self, _ctx: Context) -> f32 {
+        0.0
+    }
+
+    /// Width the widget would take if it had no constraints.
+    fn max_intrinsic_width(&self, _ctx: Context) -> f32 {
+        f32::INFINITY
+    }
+
+    /// Smallest height the widget needs for the width in `ctx`.
+    fn min_intrinsic_height(&self, _ctx: Context) -> f32 {
+        0.0
+    }
+
+    /// Height the widget would take for the width in `ctx` if it had no constraints.
+    fn max_intrinsic_height(&self, _ctx: Context) -> f32 {
+        f32::INFINITY
+    }
+
+    /// Distance from the top of the widget to its first text baseline, if it has any.
+    fn baseline(&self, _ctx: Context) -> Option<f32> {
+        None
+    }
 }
 
 pub trait AppWindow {
@@ -47,28 +312,125 @@ pub trait AppWindow {
 
 pub struct App {
     windows: Vec<Window>,
+    fonts: Vec<(String, Typeface)>,
+    keymap: Keymap,
 }
 
 impl App {
     pub fn new() -> Self {
-        Self { windows: vec![] }
+        let mut keymap = Keymap::new();
+        keymap.bind(
+            KeyChord::new(VirtualKeyCode::Q)
+                .logo()
+                .into(),
+            "quit",
+        );
+        keymap.action("quit", quit);
+
+        Self {
+            windows: vec![],
+            fonts: vec![],
+            keymap,
+        }
     }
 
     pub fn with_window(mut self, window: Window) -> Self {
         self.windows.push(window);
         self
     }
+
+    /// Register a font (e.g. from `include_bytes!`) so `TextStyle::family` can use it by `family`.
+    /// Fails with `InvalidData` if `data` isn't a font that can be read.
+    pub fn with_font(mut self, family: &str, data: &[u8]) -> io::Result<Self> {
+        let typeface = text_layout::load_font(data).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Couldn't load font for family {}", family),
+            )
+        })?;
+        self.fonts
+            .push((family.to_string(), typeface));
+        Ok(self)
+    }
+
+    /// Like `with_font`, but reads the font from a file.
+    pub fn with_font_file(self, family: &str, path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        self.with_font(family, &data)
+    }
+
+    /// Run `action` when `sequence` is typed and no window or widget handles it. Binding
+    /// a sequence again replaces its action, e.g. for the default Cmd+Q "quit".
+    pub fn with_shortcut(mut self, sequence: impl Into<Vec<KeyChord>>, action: &str) -> Self {
+        self.keymap
+            .bind(sequence.into(), action);
+        self
+    }
+
+    pub fn without_shortcut(mut self, sequence: impl Into<Vec<KeyChord>>) -> Self {
+        self.keymap.unbind(&sequence.into());
+        self
+    }
+
+    /// Define what the action called `name` does, replacing what it did.
+    pub fn with_action(mut self, name: &str, callback: impl FnMut() + 'static) -> Self {
+        self.keymap.action(name, callback);
+        self
+    }
+
+    /// App-wide bindings, e.g. to list them in a help overlay.
+    pub fn bindings(&self) -> &[Binding] {
+        self.keymap.bindings()
+    }
 }
 
 pub struct Window {
     title: String,
     root: Option<Box<dyn Widget>>,
+    direction: TextDirection,
+    cursor: Option<(f64, f64)>,
+    /// Whether the cursor is over the window, it's still known outside of it while the
+    /// mouse is captured
+    cursor_inside: bool,
+    modifiers: ModifiersState,
+    last_click: Option<Click>,
+    /// Hit path the cursor is over
+    hovered: Option<Vec<usize>>,
+    /// Hit path of the widget capturing each pointer, the pointers held down are captured
+    /// by the widgets they were pressed on unless one of those captured it
+    captured: HashMap<PointerId, Vec<usize>>,
+    /// Mouse buttons held down, in the order they were pressed
+    held_buttons: Vec<MouseButton>,
+    /// Hit path the drag in progress is over
+    drag_hovered: Option<Vec<usize>>,
+    /// Files of the last drop from another app
+    dropped_files: Vec<PathBuf>,
+    /// Last known cursor position, where files dragged from another app are
+    file_position: (f64, f64),
+    held_keys: Vec<VirtualKeyCode>,
+    /// Focus the widgets were last told about
+    focused: Option<FocusId>,
+    keymap: Keymap,
+    /// Plain chord pressed in a text widget, its shortcut waits for the text it types
+    typing: Option<KeyChord>,
 }
 
 impl Widget for Window {
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
         if let Some(root) = &mut self.root {
-            root.draw(canvas, ctx.clone());
+            // The focus may have been requested outside of an event
+            Self::notify_focus(root.as_mut(), &mut self.focused);
+
+            root.draw(
+                canvas,
+                Context {
+                    direction: self.direction,
+                    ..ctx
+                },
+            );
+
+            Self::draw_focus_ring(root.as_mut(), canvas);
+            drag::draw_feedback(canvas, ctx);
         }
     }
 }
@@ -84,6 +446,21 @@ impl Window {
         Self {
             title: "Test".to_string(),
             root: None,
+            direction: TextDirection::Ltr,
+            cursor: None,
+            cursor_inside: false,
+            modifiers: ModifiersState::empty(),
+            last_click: None,
+            hovered: None,
+            captured: HashMap::new(),
+            held_buttons: vec![],
+            drag_hovered: None,
+            dropped_files: vec![],
+            file_position: (0.0, 0.0),
+            held_keys: vec![],
+            focused: None,
+            keymap: Keymap::new(),
+            typing: None,
         }
     }
 
@@ -92,35 +469,821 @@ impl Window {
         self
     }
 
+    /// Direction inherited by the whole widget tree, unless overridden with `Directionality`.
+    pub fn with_direction(mut self, direction: TextDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
     pub fn root(mut self, child: impl Widget + 'static) -> Self {
         self.root = Some(Box::new(child));
         self
     }
 
-    fn send_event(&mut self, event: &YalemEvent) {
-        if let Some(child) = &mut self.root {
-            child.send_event(&event)
+    /// Run `action` when `sequence` is typed in this window and no widget handles it.
+    /// Window shortcuts come before the app's.
+    pub fn with_shortcut(mut self, sequence: impl Into<Vec<KeyChord>>, action: &str) -> Self {
+        self.keymap
+            .bind(sequence.into(), action);
+        self
+    }
+
+    /// Define what the action called `name` does in this window.
+    pub fn with_action(mut self, name: &str, callback: impl FnMut() + 'static) -> Self {
+        self.keymap.action(name, callback);
+        self
+    }
+
+    /// Window bindings, e.g. to list them in a help overlay.
+    pub fn bindings(&self) -> &[Binding] {
+        self.keymap.bindings()
+    }
+
+    /// Turn a raw pointer event into a `YalemMouse` one, keeping track of the cursor,
+    /// the modifiers and repeated clicks.
+    fn mouse_event(&mut self, event: &WindowEvent) -> Option<YalemMouse> {
+        let modifiers = self.modifiers;
+
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+                None
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = (position.x, position.y);
+                self.cursor = Some(position);
+                if !self
+                    .captured
+                    .contains_key(&PointerId::Mouse)
+                {
+                    self.cursor_inside = true;
+                }
+                Some(YalemMouse::Moved {
+                    pointer: PointerId::Mouse,
+                    position,
+                    modifiers,
+                })
+            }
+            WindowEvent::CursorEntered { .. } => {
+                self.cursor_inside = true;
+                None
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_inside = false;
+                if !self
+                    .captured
+                    .contains_key(&PointerId::Mouse)
+                {
+                    self.cursor = None;
+                }
+                None
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => {
+                let position = self.cursor?;
+                Some(YalemMouse::Pressed {
+                    pointer: PointerId::Mouse,
+                    button: *button,
+                    position,
+                    modifiers,
+                    click_count: self.click_count(PointerId::Mouse, *button, position),
+                })
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } => Some(YalemMouse::Released {
+                pointer: PointerId::Mouse,
+                button: *button,
+                position: self.cursor?,
+                modifiers,
+            }),
+            WindowEvent::MouseWheel { delta, .. } => Some(YalemMouse::Wheel {
+                delta: *delta,
+                position: self.cursor?,
+                modifiers,
+            }),
+            // A finger acts like the left button of a pointer of its own
+            WindowEvent::Touch(Touch {
+                phase,
+                location,
+                id,
+                ..
+            }) => {
+                let pointer = PointerId::Touch(*id);
+                let position = (location.x, location.y);
+
+                Some(match phase {
+                    TouchPhase::Started => YalemMouse::Pressed {
+                        pointer,
+                        button: MouseButton::Left,
+                        position,
+                        modifiers,
+                        click_count: self.click_count(pointer, MouseButton::Left, position),
+                    },
+                    TouchPhase::Moved => YalemMouse::Moved {
+                        pointer,
+                        position,
+                        modifiers,
+                    },
+                    TouchPhase::Ended => YalemMouse::Released {
+                        pointer,
+                        button: MouseButton::Left,
+                        position,
+                        modifiers,
+                    },
+                    TouchPhase::Cancelled => YalemMouse::Cancelled { pointer, position },
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of presses in a row of `button` around `position`, counting this one.
+    /// Fingers get a new id for each touch, so taps follow each other whatever the
+    /// finger, but not clicks of the mouse.
+    fn click_count(
+        &mut self,
+        pointer: PointerId,
+        button: MouseButton,
+        position: (f64, f64),
+    ) -> u32 {
+        let now = Instant::now();
+        let click_count = match self.last_click {
+            Some(last)
+                if last.pointer.is_touch() == pointer.is_touch()
+                    && last.button == button
+                    && now - last.time < DOUBLE_CLICK_INTERVAL
+                    && (last.position.0 - position.0).abs() <= CLICK_SLOP
+                    && (last.position.1 - position.1).abs() <= CLICK_SLOP =>
+            {
+                last.count + 1
+            }
+            _ => 1,
+        };
+        self.last_click = Some(Click {
+            pointer,
+            button,
+            position,
+            time: now,
+            count: click_count,
+        });
+
+        click_count
+    }
+
+    /// Turn a raw keyboard event into a `KeyEvent`, keeping track of the held keys.
+    fn key_event(&mut self, event: &WindowEvent) -> Option<KeyEvent> {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode,
+                        ..
+                    },
+                ..
+            } => {
+                let mut repeat = false;
+                if let Some(key) = virtual_keycode {
+                    let held = self.held_keys.contains(key);
+                    match state {
+                        ElementState::Pressed if held => repeat = true,
+                        ElementState::Pressed => self.held_keys.push(*key),
+                        ElementState::Released => self
+                            .held_keys
+                            .retain(|held| held != key),
+                    }
+                }
+
+                Some(KeyEvent {
+                    key: *virtual_keycode,
+                    state: *state,
+                    text: None,
+                    modifiers: self.modifiers,
+                    repeat,
+                })
+            }
+            // Control characters come with keys like Tab and Backspace, they aren't text
+            WindowEvent::ReceivedCharacter(c) if !c.is_control() => Some(KeyEvent {
+                key: None,
+                state: ElementState::Pressed,
+                text: Some(c.to_string()),
+                modifiers: self.modifiers,
+                repeat: false,
+            }),
+            // Releases are missed while another window has the focus
+            WindowEvent::Focused(false) => {
+                self.held_keys.clear();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the chords typed that neither the widgets nor the window shortcuts
+    /// handled, for the app shortcuts.
+    fn send_event(&mut self, event: &YalemEvent) -> Vec<KeyChord> {
+        let (mouse, key) = match event {
+            YalemEvent::Winit(event) => (self.mouse_event(event), self.key_event(event)),
+            _ => (None, None),
+        };
+
+        let root = match &mut self.root {
+            Some(root) => root.as_mut(),
+            None => return vec![],
+        };
+        let mut unhandled = vec![];
+
+        // No text followed the plain chord, it was a shortcut
+        let text = matches!(&key, Some(KeyEvent { text: Some(_), .. }));
+        if !text {
+            if let Some(chord) = self.typing.take() {
+                Self::handle_chord(&mut self.keymap, chord, &mut unhandled);
+            }
+        }
+
+        if let YalemEvent::Winit(WindowEvent::CursorMoved { .. } | WindowEvent::CursorLeft { .. }) =
+            event
+        {
+            Self::update_hover(root, &mut self.hovered, self.cursor);
+        }
+
+        // Files dragged from another app follow the cursor. Some platforms don't report
+        // the cursor while they're over the window, the last place it was known is used,
+        // or the top-left corner if it never was.
+        // Each file comes in its own event, the hovered ones are all dropped with the
+        // first and the events of the others are skipped.
+        if !matches!(event, YalemEvent::Winit(WindowEvent::DroppedFile(_))) {
+            self.dropped_files.clear();
+        }
+        if let Some(position) = self.cursor {
+            self.file_position = position;
+        }
+        let position = self.file_position;
+        match event {
+            YalemEvent::Winit(WindowEvent::HoveredFile(path)) => {
+                drag::hover_file(path.clone(), position);
+            }
+            YalemEvent::Winit(WindowEvent::DroppedFile(path))
+                if !self.dropped_files.contains(path) =>
+            {
+                let mut files = drag::files().unwrap_or_default();
+                if !files.contains(path) {
+                    files.push(path.clone());
+                }
+                self.dropped_files = files.clone();
+                drag::start(Box::new(files), None, position, None);
+                drag::drop_at(position);
+            }
+            YalemEvent::Winit(WindowEvent::HoveredFileCancelled) => drag::cancel(),
+            YalemEvent::Winit(WindowEvent::CursorMoved { .. }) if drag::pointer() == Some(None) => {
+                drag::move_to(position);
+            }
+            _ => {}
+        }
+
+        if let Some(mouse) = mouse {
+            // Clicking anywhere else blurs the focused widget, unless another pointer
+            // is already down, e.g. the first finger of a pinch
+            if let YalemMouse::Pressed {
+                button: MouseButton::Left,
+                pointer,
+                ..
+            } = mouse
+            {
+                if self.captured.is_empty() {
+                    set_focus(None);
+                }
+                gestures::reset_arena(pointer);
+            }
+
+            // The mouse is captured by the widget its first button was pressed on, until
+            // the last one is released
+            let releases_pointer = match &mouse {
+                YalemMouse::Pressed {
+                    pointer: PointerId::Mouse,
+                    button,
+                    ..
+                } => {
+                    if !self
+                        .captured
+                        .contains_key(&PointerId::Mouse)
+                    {
+                        self.held_buttons.clear();
+                    }
+                    self.held_buttons.push(*button);
+                    false
+                }
+                YalemMouse::Released {
+                    pointer: PointerId::Mouse,
+                    button,
+                    ..
+                } => {
+                    self.held_buttons
+                        .retain(|held| held != button);
+                    self.held_buttons.is_empty()
+                }
+                YalemMouse::Released { .. } | YalemMouse::Cancelled { .. } => true,
+                _ => false,
+            };
+
+            // The moves of a captured pointer go to the widget capturing it, even outside
+            // of it, and its releases reach each widget of the capture so none is left
+            // waiting for them
+            let captured_path = match &mouse {
+                YalemMouse::Released { pointer, .. } | YalemMouse::Cancelled { pointer, .. }
+                    if releases_pointer =>
+                {
+                    self.captured.remove(pointer)
+                }
+                YalemMouse::Moved { pointer, .. } | YalemMouse::Released { pointer, .. } => {
+                    self.captured.get(pointer).cloned()
+                }
+                _ => None,
+            };
+            let press = match mouse {
+                YalemMouse::Pressed { pointer, .. } => Some(pointer),
+                _ => None,
+            };
+            let ends_press = matches!(
+                mouse,
+                YalemMouse::Released { .. } | YalemMouse::Cancelled { .. }
+            );
+
+            let position = mouse.position();
+            let event = YalemEvent::YalemMouse(mouse);
+            match captured_path {
+                Some(path) if ends_press => dispatch::deliver(root, &path, &event),
+                Some(path) => {
+                    dispatch::dispatch(root, &path, &event);
+                }
+                None => {
+                    let mut path = vec![];
+                    if dispatch::hit_test(root, position, &mut path) {
+                        if let Some(pointer) = press {
+                            self.captured
+                                .entry(pointer)
+                                .or_insert_with(|| path.clone());
+                        }
+                        dispatch::dispatch(root, &path, &event);
+                    }
+                }
+            }
+
+            if !ends_press {
+                Self::update_captures(root, &mut self.captured, position);
+            }
+
+            // Released outside of the window
+            if !self.cursor_inside
+                && !self
+                    .captured
+                    .contains_key(&PointerId::Mouse)
+            {
+                self.cursor = None;
+                Self::update_hover(root, &mut self.hovered, self.cursor);
+            }
+        }
+        // Captures asked for during other events, or releases, have nothing to apply to
+        dispatch::take_captures();
+
+        if let Some(key) = key {
+            let tab = key.key == Some(VirtualKeyCode::Tab)
+                && key.state == ElementState::Pressed
+                && !key.modifiers.ctrl()
+                && !key.modifiers.alt()
+                && !key.modifiers.logo();
+            let backwards = key.modifiers.shift();
+            let chord = KeyChord::from_event(&key);
+
+            // Keys go to the focused widget and bubble up from there, or only go
+            // to the root when nothing has the focus
+            let event = YalemEvent::Key(key);
+            let mut path = vec![];
+            let mut accepts_text = false;
+            if let Some(id) = focused() {
+                if dispatch::focus_path(root, id, &mut path) {
+                    accepts_text =
+                        dispatch::find(root, &path).is_some_and(|widget| widget.accepts_text());
+                } else {
+                    path.clear();
+                }
+            }
+
+            let handled = dispatch::dispatch(root, &path, &event);
+            if text {
+                // Text typed by a plain chord, its shortcut only runs if no widget took it
+                if let Some(chord) = self.typing.take() {
+                    if !handled {
+                        Self::handle_chord(&mut self.keymap, chord, &mut unhandled);
+                    }
+                }
+            } else if !handled {
+                if tab {
+                    Self::move_focus(root, backwards);
+                } else if let Some(chord) = chord {
+                    // A plain chord may type text, which comes next
+                    let modifiers = chord.modifiers();
+                    if accepts_text && !modifiers.ctrl() && !modifiers.alt() && !modifiers.logo() {
+                        self.typing = Some(chord);
+                    } else {
+                        Self::handle_chord(&mut self.keymap, chord, &mut unhandled);
+                    }
+                }
+            }
+        }
+
+        // Wakes, and raw events with no position or focused widget to go to, reach
+        // every widget
+        if matches!(
+            event,
+            YalemEvent::Wake
+                | YalemEvent::Winit(
+                    WindowEvent::ModifiersChanged(_)
+                        | WindowEvent::Focused(_)
+                        | WindowEvent::Ime(_)
+                        | WindowEvent::Resized(_)
+                )
+        ) {
+            dispatch::broadcast(root, event);
+        }
+
+        if drag::take_changed() {
+            Self::update_drag(root, &mut self.drag_hovered);
+        }
+
+        Self::notify_focus(root, &mut self.focused);
+
+        unhandled
+    }
+
+    /// Run the window shortcut for `chord`, or leave it to the app's.
+    fn handle_chord(keymap: &mut Keymap, chord: KeyChord, unhandled: &mut Vec<KeyChord>) {
+        if !keymap.handle(chord) {
+            unhandled.push(chord);
+        }
+    }
+
+    /// Move the focus to the next widget in tab order, or the previous one.
+    fn move_focus(root: &mut dyn Widget, backwards: bool) {
+        let mut order = vec![];
+        dispatch::tab_order(root, &mut order);
+        if order.is_empty() {
+            return;
         }
+
+        let current = focused().and_then(|id| {
+            order
+                .iter()
+                .position(|other| *other == id)
+        });
+        let next = match (current, backwards) {
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+        };
+        set_focus(Some(order[next]));
+    }
+
+    /// Send `Blurred` and `Focused` if the focus moved since `notified`.
+    fn notify_focus(root: &mut dyn Widget, notified: &mut Option<FocusId>) {
+        let now_focused = focused();
+        if now_focused == *notified {
+            return;
+        }
+
+        for (id, event) in [
+            (*notified, YalemEvent::Blurred),
+            (now_focused, YalemEvent::Focused),
+        ] {
+            let mut path = vec![];
+            if id.is_some_and(|id| dispatch::focus_path(root, id, &mut path)) {
+                dispatch::send_to(root, &path, &event);
+            }
+        }
+
+        *notified = now_focused;
+    }
+
+    fn draw_focus_ring(root: &mut dyn Widget, canvas: &mut Canvas) {
+        let mut path = vec![];
+        let ring = focused()
+            .filter(|id| dispatch::focus_path(root, *id, &mut path))
+            .and_then(|_| dispatch::find(root, &path))
+            .and_then(|widget| widget.focus_ring());
+
+        if let Some(ring) = ring {
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_width(FOCUS_RING_WIDTH);
+            paint.set_color(FOCUS_RING_COLOR);
+            canvas.draw_rect(
+                ring.with_outset((FOCUS_RING_WIDTH, FOCUS_RING_WIDTH)),
+                &paint,
+            );
+        }
+    }
+
+    /// Send `Exited` to the widgets the cursor left and `Entered` to the ones it is now over.
+    /// `hovered` is the hit path the cursor was last over.
+    fn update_hover(
+        root: &mut dyn Widget,
+        hovered: &mut Option<Vec<usize>>,
+        cursor: Option<(f64, f64)>,
+    ) {
+        let mut path = vec![];
+        let now_hovered = cursor
+            .filter(|position| dispatch::hit_test(root, *position, &mut path))
+            .map(|_| path);
+        if now_hovered == *hovered {
+            return;
+        }
+
+        let kept = shared_depth(hovered.as_ref(), now_hovered.as_ref());
+        let position = cursor.unwrap_or_default();
+
+        if let Some(old) = &*hovered {
+            // Innermost first
+            for depth in (kept..=old.len()).rev() {
+                let event = YalemEvent::YalemMouse(YalemMouse::Exited { position });
+                dispatch::send_to(root, &old[..depth], &event);
+            }
+        }
+
+        if let Some(new) = &now_hovered {
+            for depth in kept..=new.len() {
+                let event = YalemEvent::YalemMouse(YalemMouse::Entered { position });
+                dispatch::send_to(root, &new[..depth], &event);
+            }
+        }
+
+        *hovered = now_hovered;
+    }
+
+    /// Apply the captures asked for while handling a pointer event at `position`. The
+    /// widgets a capture leaves out get `Cancelled`, as they won't see the release.
+    fn update_captures(
+        root: &mut dyn Widget,
+        captured: &mut HashMap<PointerId, Vec<usize>>,
+        position: (f64, f64),
+    ) {
+        for (pointer, path) in dispatch::take_captures() {
+            let old = match path {
+                Some(path) => captured.insert(pointer, path),
+                None => captured.remove(&pointer),
+            };
+            let old = match (old, captured.get(&pointer)) {
+                (Some(old), Some(new)) if old != *new => old,
+                _ => continue,
+            };
+
+            // Innermost first
+            let kept = shared_depth(Some(&old), captured.get(&pointer));
+            for depth in (kept..=old.len()).rev() {
+                let event = YalemEvent::YalemMouse(YalemMouse::Cancelled { pointer, position });
+                dispatch::send_to(root, &old[..depth], &event);
+            }
+        }
+    }
+
+    /// Cursor of the innermost widget that has one, under a drag, held down by the mouse
+    /// or under the mouse, in that order.
+    fn cursor_icon(&mut self) -> CursorIcon {
+        let root = match &mut self.root {
+            Some(root) => root.as_mut(),
+            None => return CursorIcon::Default,
+        };
+
+        [
+            self.drag_hovered.as_ref(),
+            self.captured.get(&PointerId::Mouse),
+            self.hovered.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|path| {
+            (0..=path.len())
+                .rev()
+                .find_map(|depth| dispatch::find(root, &path[..depth])?.cursor())
+        })
+        .unwrap_or_default()
+    }
+
+    /// Send the drag in progress to the drop targets under it, and drop it once it's
+    /// released. `hovered` is the hit path it was last over.
+    fn update_drag(root: &mut dyn Widget, hovered: &mut Option<Vec<usize>>) {
+        let dragged = drag::take();
+
+        let mut path = vec![];
+        let now_hovered = dragged
+            .as_ref()
+            .filter(|dragged| {
+                dragged.state != DragState::Cancelled
+                    && dispatch::hit_test(root, dragged.position, &mut path)
+            })
+            .map(|_| path);
+        let kept = shared_depth(hovered.as_ref(), now_hovered.as_ref());
+
+        if let Some(old) = &*hovered {
+            // Innermost first
+            for depth in (kept..=old.len()).rev() {
+                dispatch::send_to(root, &old[..depth], &YalemEvent::DragLeft);
+            }
+        }
+        *hovered = now_hovered;
+
+        let dragged = match dragged {
+            Some(dragged) => dragged,
+            None => return,
+        };
+
+        if let Some(path) = &*hovered {
+            let payload = dragged.payload.as_ref();
+            let position = dragged.position;
+            let event = match dragged.state {
+                DragState::Dropped => YalemEvent::Drop { payload, position },
+                _ => YalemEvent::DragOver { payload, position },
+            };
+            dispatch::dispatch(root, path, &event);
+        }
+
+        if dragged.state == DragState::Dragging {
+            drag::restore(dragged);
+        } else if let Some(path) = hovered.take() {
+            for depth in (0..=path.len()).rev() {
+                dispatch::send_to(root, &path[..depth], &YalemEvent::DragLeft);
+            }
+        }
+    }
+}
+
+/// Number of widgets two hit paths have in common, counting the root.
+fn shared_depth(old: Option<&Vec<usize>>, new: Option<&Vec<usize>>) -> usize {
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            1 + old
+                .iter()
+                .zip(new)
+                .take_while(|(a, b)| a == b)
+                .count()
+        }
+        _ => 0,
+    }
+}
+
+/// Press that can be followed by another one to make a double click.
+#[derive(Clone, Copy)]
+struct Click {
+    pointer: PointerId,
+    button: MouseButton,
+    position: (f64, f64),
+    time: Instant,
+    count: u32,
+}
+
+/// Mouse or finger on a touch screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerId {
+    Mouse,
+    /// Finger, identified for as long as it touches the screen
+    Touch(u64),
+}
+
+impl PointerId {
+    pub fn is_touch(self) -> bool {
+        matches!(self, Self::Touch(_))
     }
 }
 
+/// Pointer event, sent to the widgets under the pointer. Once a pointer is pressed, its
+/// moves and release go to the widgets it was pressed on instead.
 #[derive(Debug)]
 pub enum YalemMouse {
     Pressed {
+        pointer: PointerId,
+        button: MouseButton,
+        position: (f64, f64),
+        modifiers: ModifiersState,
+        /// 2 for a double click, 3 for a triple click, and so on
+        click_count: u32,
+    },
+    Released {
+        pointer: PointerId,
         button: MouseButton,
         position: (f64, f64),
+        modifiers: ModifiersState,
+    },
+    Moved {
+        pointer: PointerId,
+        position: (f64, f64),
+        modifiers: ModifiersState,
+    },
+    /// The system took over a touch, e.g. for a gesture of its own. It ends the
+    /// press like a release, without a click.
+    Cancelled {
+        pointer: PointerId,
+        position: (f64, f64),
+    },
+    /// The pointer moved over the widget. Only sent to the widget itself, not its ancestors.
+    Entered { position: (f64, f64) },
+    /// The pointer left the widget. Only sent to the widget itself, not its ancestors.
+    Exited { position: (f64, f64) },
+    Wheel {
+        delta: MouseScrollDelta,
+        position: (f64, f64),
+        modifiers: ModifiersState,
     },
 }
 
+impl YalemMouse {
+    /// Position of the pointer in the window.
+    pub fn position(&self) -> (f64, f64) {
+        match self {
+            YalemMouse::Pressed { position, .. }
+            | YalemMouse::Released { position, .. }
+            | YalemMouse::Moved { position, .. }
+            | YalemMouse::Cancelled { position, .. }
+            | YalemMouse::Entered { position }
+            | YalemMouse::Exited { position }
+            | YalemMouse::Wheel { position, .. } => *position,
+        }
+    }
+
+    /// Pointer of the event, `None` for the ones only the mouse sends.
+    pub fn pointer(&self) -> Option<PointerId> {
+        match self {
+            YalemMouse::Pressed { pointer, .. }
+            | YalemMouse::Released { pointer, .. }
+            | YalemMouse::Moved { pointer, .. }
+            | YalemMouse::Cancelled { pointer, .. } => Some(*pointer),
+            YalemMouse::Entered { .. } | YalemMouse::Exited { .. } | YalemMouse::Wheel { .. } => {
+                None
+            }
+        }
+    }
+}
+
+/// Key press or release, sent to the focused widget and then to its ancestors.
+#[derive(Debug)]
+pub struct KeyEvent {
+    /// `None` for typed text, and for keys winit doesn't know
+    pub key: Option<VirtualKeyCode>,
+    pub state: ElementState,
+    /// Text typed. winit reports it apart from the key that typed it, so it comes in
+    /// an event of its own.
+    pub text: Option<String>,
+    pub modifiers: ModifiersState,
+    /// Whether the key is held down and this is a repeat of its press
+    pub repeat: bool,
+}
+
 #[derive(Debug)]
 pub enum YalemEvent<'a> {
     YalemMouse(YalemMouse),
+    Key(KeyEvent),
+    /// The widget took the keyboard focus. Only sent to the widget itself.
+    Focused,
+    /// The widget lost the keyboard focus. Only sent to the widget itself.
+    Blurred,
+    /// A time asked for with `request_redraw_at` was reached, sent to every widget
+    /// right before the redraw.
+    Wake,
+    /// A drag is over the widget, sent like pointer events. The drop target that
+    /// would take the payload handles it.
+    DragOver {
+        payload: &'a dyn Any,
+        position: (f64, f64),
+    },
+    /// The drag left the widget, or ended. Only sent to the widget itself.
+    DragLeft,
+    /// The payload was dropped over the widget, sent like pointer events until a drop
+    /// target takes it. Files dropped from another app are a `Vec<PathBuf>`.
+    Drop {
+        payload: &'a dyn Any,
+        position: (f64, f64),
+    },
+    /// Window events with no target, i.e. `ModifiersChanged`, `Focused`, `Ime` and
+    /// `Resized`, sent to every widget. The others arrive as the events above.
     Winit(WindowEvent<'a>),
 }
 
 pub fn run(app: App) {
     type WindowedContext = glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>;
 
+    // Makes the data bidi and line breaking rely on available on Windows
+    skia_safe::icu::init();
+
+    for (family, typeface) in &app.fonts {
+        text_layout::register_font(family, typeface.clone());
+    }
+
     let el = EventLoop::new();
 
     // Guarantee the drop order inside the FnMut closure. `WindowedContext` _must_ be dropped after
@@ -133,6 +1296,8 @@ pub fn run(app: App) {
         windowed_context: WindowedContext,
         yalem_window: Window,
         fb_info: FramebufferInfo,
+        ime_caret: Option<Rect>,
+        cursor_icon: CursorIcon,
     }
 
     impl Env {
@@ -141,6 +1306,8 @@ pub fn run(app: App) {
                 .windowed_context
                 .window()
                 .inner_size();
+            IME_CARET.with(|ime_caret| ime_caret.set(None));
+
             let canvas = self.surface.canvas();
             canvas.clear(Color::WHITE);
             self.yalem_window.draw(
@@ -150,16 +1317,49 @@ pub fn run(app: App) {
                     y: 0.0,
                     height: win_size.height as f32,
                     width: win_size.width as f32,
+                    direction: TextDirection::Ltr,
                 },
             );
+            self.update_ime();
             self.surface.canvas();
             self.gr_context.flush(None);
             self.windowed_context
                 .swap_buffers()
                 .unwrap();
         }
+
+        /// Show the cursor the widgets under the mouse ask for.
+        fn update_cursor_icon(&mut self) {
+            let cursor_icon = self.yalem_window.cursor_icon();
+            if cursor_icon != self.cursor_icon {
+                self.windowed_context
+                    .window()
+                    .set_cursor_icon(cursor_icon);
+                self.cursor_icon = cursor_icon;
+            }
+        }
+
+        /// Only let input methods compose text while a text widget is focused,
+        /// and keep their candidate window at its caret.
+        fn update_ime(&mut self) {
+            let ime_caret = IME_CARET.with(|ime_caret| ime_caret.take());
+            if ime_caret == self.ime_caret {
+                return;
+            }
+
+            let window = self.windowed_context.window();
+            if ime_caret.is_some() != self.ime_caret.is_some() {
+                window.set_ime_allowed(ime_caret.is_some());
+            }
+            if let Some(caret) = ime_caret {
+                window.set_ime_position(PhysicalPosition::new(caret.left, caret.bottom));
+            }
+
+            self.ime_caret = ime_caret;
+        }
     }
 
+    let mut app_keymap = app.keymap;
     let wins = Arc::new(Mutex::new(vec![]));
 
     for win in app.windows {
@@ -206,6 +1406,8 @@ pub fn run(app: App) {
             windowed_context,
             fb_info,
             yalem_window: win,
+            ime_caret: None,
+            cursor_icon: CursorIcon::Default,
         };
 
         wins.lock()
@@ -245,6 +1447,8 @@ pub fn run(app: App) {
         .unwrap()
     }
 
+    let all_windows = wins.clone();
+
     let get_window_context = move |window_id: WindowId| -> Option<Arc<Mutex<Env>>> {
         let mut win = None;
         for env in &*wins.lock().unwrap() {
@@ -263,72 +1467,88 @@ pub fn run(app: App) {
         win
     };
 
-    let mut cursor_pos = (0.0, 0.0);
-
     el.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Wait;
 
         #[allow(deprecated)]
         match event {
             Event::LoopDestroyed => {}
-            Event::WindowEvent { event, window_id } => match event {
-                WindowEvent::CursorMoved { position, .. } => {
-                    cursor_pos = (position.x, position.y);
-                }
-                WindowEvent::MouseInput { button, state, .. } => {
-                    if ElementState::Pressed == state {
-                        let result = get_window_context(window_id);
-                        if let Some(env) = result {
-                            let mut env = env.lock().unwrap();
-                            env.yalem_window
-                                .send_event(&YalemEvent::YalemMouse(YalemMouse::Pressed {
-                                    position: cursor_pos,
-                                    button,
-                                }));
-                            env.redraw();
-                        }
+            Event::NewEvents(StartCause::ResumeTimeReached { .. }) => {
+                NEXT_REDRAW.with(|next_redraw| next_redraw.set(None));
+                for env in &*all_windows.lock().unwrap() {
+                    let mut env = env.lock().unwrap();
+                    let unhandled = env
+                        .yalem_window
+                        .send_event(&YalemEvent::Wake);
+                    for chord in unhandled {
+                        app_keymap.handle(chord);
                     }
+                    shortcuts::run_triggered_actions(&mut env.yalem_window.keymap, &mut app_keymap);
+                    env.windowed_context
+                        .window()
+                        .request_redraw();
                 }
-                WindowEvent::Resized(physical_size) => {
+            }
+            Event::WindowEvent { event, window_id } => {
+                // Forward the raw event to the window's widgets
+                let mut send_winit_event = |event: WindowEvent| {
                     let result = get_window_context(window_id);
                     if let Some(env) = result {
                         let mut env = env.lock().unwrap();
-                        let mut context = env.gr_context.clone();
-                        env.surface =
-                            create_surface(&env.windowed_context, &env.fb_info, &mut context);
-                        env.windowed_context
-                            .resize(physical_size)
-                    }
-                }
-                WindowEvent::CloseRequested => {
-                    // should only remove one window
-                    *control_flow = ControlFlow::Exit
-                }
-                WindowEvent::KeyboardInput {
-                    input:
-                        KeyboardInput {
-                            virtual_keycode,
-                            modifiers,
-                            ..
-                        },
-                    ..
-                } => {
-                    if modifiers.logo() {
-                        if let Some(VirtualKeyCode::Q) = virtual_keycode {
-                            *control_flow = ControlFlow::Exit;
+                        let unhandled = env
+                            .yalem_window
+                            .send_event(&YalemEvent::Winit(event));
+
+                        for chord in unhandled {
+                            app_keymap.handle(chord);
                         }
-                    }
+                        shortcuts::run_triggered_actions(
+                            &mut env.yalem_window.keymap,
+                            &mut app_keymap,
+                        );
+                        env.update_cursor_icon();
 
-                    let result = get_window_context(window_id);
-                    if let Some(env) = result {
-                        let env = env.lock().unwrap();
                         env.windowed_context
                             .window()
                             .request_redraw();
                     }
+                };
+
+                match event {
+                    WindowEvent::CursorMoved { .. }
+                    | WindowEvent::CursorEntered { .. }
+                    | WindowEvent::CursorLeft { .. }
+                    | WindowEvent::MouseInput { .. }
+                    | WindowEvent::ReceivedCharacter(_)
+                    | WindowEvent::ModifiersChanged(_)
+                    | WindowEvent::MouseWheel { .. }
+                    | WindowEvent::Touch(_)
+                    | WindowEvent::HoveredFile(_)
+                    | WindowEvent::HoveredFileCancelled
+                    | WindowEvent::DroppedFile(_)
+                    | WindowEvent::Focused(_)
+                    | WindowEvent::KeyboardInput { .. }
+                    | WindowEvent::Ime(_) => {
+                        send_winit_event(event);
+                    }
+                    WindowEvent::Resized(physical_size) => {
+                        let result = get_window_context(window_id);
+                        if let Some(env) = result {
+                            let mut env = env.lock().unwrap();
+                            let mut context = env.gr_context.clone();
+                            env.surface =
+                                create_surface(&env.windowed_context, &env.fb_info, &mut context);
+                            env.windowed_context
+                                .resize(physical_size)
+                        }
+                    }
+                    WindowEvent::CloseRequested => {
+                        // should only remove one window
+                        *control_flow = ControlFlow::Exit
+                    }
+                    _ => (),
                 }
-                _ => (),
-            },
+            }
             Event::RedrawRequested(window_id) => {
                 let result = get_window_context(window_id);
                 if let Some(env) = result {
@@ -338,5 +1558,154 @@ pub fn run(app: App) {
             }
             _ => (),
         }
+
+        if QUIT.with(|quit| quit.get()) {
+            *control_flow = ControlFlow::Exit;
+        }
+
+        if *control_flow == ControlFlow::Wait {
+            if let Some(next_redraw) = NEXT_REDRAW.with(|next_redraw| next_redraw.get()) {
+                *control_flow = ControlFlow::WaitUntil(next_redraw);
+            }
+        }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use glutin::event::DeviceId;
+
+    use super::*;
+    use widgets::TextInput;
+
+    fn press(window: &mut Window, key: VirtualKeyCode, text: Option<char>) -> Vec<KeyChord> {
+        let mut unhandled = vec![];
+        for state in [ElementState::Pressed, ElementState::Released] {
+            #[allow(deprecated)]
+            let input = WindowEvent::KeyboardInput {
+                device_id: unsafe { DeviceId::dummy() },
+                input: KeyboardInput {
+                    scancode: 0,
+                    state,
+                    virtual_keycode: Some(key),
+                    modifiers: ModifiersState::empty(),
+                },
+                is_synthetic: false,
+            };
+            unhandled.extend(window.send_event(&YalemEvent::Winit(input)));
+            if let (ElementState::Pressed, Some(c)) = (state, text) {
+                let text = WindowEvent::ReceivedCharacter(c);
+                unhandled.extend(window.send_event(&YalemEvent::Winit(text)));
+            }
+        }
+        unhandled
+    }
+
+    /// Window with a text input and a shortcut on the plain A key, with how many
+    /// times it ran and the input's value.
+    fn typing_window() -> (Window, FocusId, Rc<Cell<usize>>, Rc<RefCell<String>>) {
+        let id = FocusId::new();
+        let value = Rc::new(RefCell::new(String::new()));
+        let changed = value.clone();
+        let runs = Rc::new(Cell::new(0));
+        let counter = runs.clone();
+        let window = Window::new()
+            .root(
+                TextInput::builder()
+                    .focus_id(id)
+                    .on_change(move |value| *changed.borrow_mut() = value.to_string())
+                    .build(),
+            )
+            .with_shortcut(KeyChord::new(VirtualKeyCode::A), "a")
+            .with_action("a", move || counter.set(counter.get() + 1));
+        (window, id, runs, value)
+    }
+
+    #[test]
+    fn typing_text_shadows_plain_shortcuts() {
+        let (mut window, id, runs, value) = typing_window();
+        set_focus(Some(id));
+
+        assert!(press(&mut window, VirtualKeyCode::A, Some('a')).is_empty());
+        assert_eq!(runs.get(), 0);
+        assert_eq!(*value.borrow(), "a");
+    }
+
+    #[test]
+    fn plain_shortcuts_run_when_no_text_is_typed() {
+        let (mut window, id, runs, value) = typing_window();
+        set_focus(Some(id));
+
+        // e.g. a dead key, or a key the layout types nothing with
+        press(&mut window, VirtualKeyCode::A, None);
+        assert_eq!(runs.get(), 1);
+        assert_eq!(*value.borrow(), "");
+
+        set_focus(None);
+        press(&mut window, VirtualKeyCode::A, Some('a'));
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[test]
+    fn unhandled_plain_chords_go_to_the_app() {
+        let (mut window, id, _, _) = typing_window();
+        set_focus(Some(id));
+
+        let unhandled = press(&mut window, VirtualKeyCode::B, None);
+        assert_eq!(unhandled, vec![KeyChord::new(VirtualKeyCode::B)]);
+    }
+
+    /// Files of each drop, and where they were dropped
+    type Drops = Rc<RefCell<Vec<(Vec<PathBuf>, (f64, f64))>>>;
+
+    /// Records the files dropped on it.
+    struct FileSink(Drops);
+
+    impl Widget for FileSink {
+        fn send_event(&mut self, event: &YalemEvent) -> bool {
+            if let YalemEvent::Drop { payload, position } = event {
+                if let Some(files) = payload.downcast_ref::<Vec<PathBuf>>() {
+                    self.0
+                        .borrow_mut()
+                        .push((files.clone(), *position));
+                }
+                return true;
+            }
+            false
+        }
+
+        fn hit_test(&self, _position: (f64, f64)) -> bool {
+            true
+        }
+
+        fn draw(&mut self, _canvas: &mut Canvas, _ctx: Context) {}
+    }
+
+    #[test]
+    fn files_drop_without_a_known_cursor() {
+        let drops = Rc::new(RefCell::new(vec![]));
+        let mut window = Window::new().root(FileSink(drops.clone()));
+
+        let file = PathBuf::from("dropped.txt");
+        window.send_event(&YalemEvent::Winit(WindowEvent::DroppedFile(file.clone())));
+        assert_eq!(*drops.borrow(), vec![(vec![file], (0.0, 0.0))]);
+    }
+
+    #[test]
+    fn shared_depth_counts_the_root() {
+        assert_eq!(shared_depth(None, None), 0);
+        assert_eq!(shared_depth(Some(&vec![0, 1]), None), 0);
+        assert_eq!(shared_depth(Some(&vec![]), Some(&vec![])), 1);
+        assert_eq!(shared_depth(Some(&vec![]), Some(&vec![2])), 1);
+    }
+
+    #[test]
+    fn shared_depth_stops_at_the_first_difference() {
+        assert_eq!(shared_depth(Some(&vec![0, 1, 2]), Some(&vec![0, 1, 2])), 4);
+        assert_eq!(shared_depth(Some(&vec![0, 1, 2]), Some(&vec![0, 1])), 3);
+        assert_eq!(shared_depth(Some(&vec![0, 1, 2]), Some(&vec![0, 3, 2])), 2);
+        assert_eq!(shared_depth(Some(&vec![1]), Some(&vec![0])), 1);
+    }
+}
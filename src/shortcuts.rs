@@ -0,0 +1,421 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use glutin::event::{ElementState, ModifiersState, VirtualKeyCode};
+
+use crate::KeyEvent;
+
+/// Longest pause between the chords of a sequence
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+thread_local! {
+    // Actions triggered by name, run by the window once the event is handled
+    static TRIGGERED: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+/// Run the action called `name`, looking it up in the focused window's shortcuts
+/// and then in the app's.
+pub fn trigger_action(name: &str) {
+    TRIGGERED.with(|triggered| {
+        triggered
+            .borrow_mut()
+            .push(name.to_string())
+    });
+}
+
+/// Run the actions triggered while handling an event, with the window's keymap or else
+/// the app's.
+pub(crate) fn run_triggered_actions(window: &mut Keymap, app: &mut Keymap) {
+    let triggered = TRIGGERED.with(|triggered| triggered.take());
+    for action in triggered {
+        if !window.run(&action) {
+            app.run(&action);
+        }
+    }
+}
+
+/// Key pressed along with some modifiers, e.g. Ctrl+S.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    key: VirtualKeyCode,
+    modifiers: ModifiersState,
+}
+
+impl KeyChord {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers |= ModifiersState::CTRL;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.modifiers |= ModifiersState::SHIFT;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.modifiers |= ModifiersState::ALT;
+        self
+    }
+
+    /// Cmd on macOS, the Windows key elsewhere.
+    pub fn logo(mut self) -> Self {
+        self.modifiers |= ModifiersState::LOGO;
+        self
+    }
+
+    /// Cmd on macOS, Ctrl elsewhere, like the platform's own shortcuts.
+    pub fn primary(self) -> Self {
+        if cfg!(target_os = "macos") {
+            self.logo()
+        } else {
+            self.ctrl()
+        }
+    }
+
+    pub fn key(&self) -> VirtualKeyCode {
+        self.key
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Chord of a key press, `None` for releases, text and modifier keys.
+    pub(crate) fn from_event(event: &KeyEvent) -> Option<Self> {
+        use VirtualKeyCode::*;
+
+        match event.key? {
+            LShift | RShift | LControl | RControl | LAlt | RAlt | LWin | RWin => None,
+            _ if event.state == ElementState::Released => None,
+            key => Some(Self {
+                key,
+                modifiers: event.modifiers,
+            }),
+        }
+    }
+}
+
+impl From<KeyChord> for Vec<KeyChord> {
+    fn from(chord: KeyChord) -> Self {
+        vec![chord]
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let logo = if cfg!(target_os = "macos") {
+            "Cmd"
+        } else {
+            "Super"
+        };
+
+        for (modifier, name) in [
+            (ModifiersState::CTRL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::LOGO, logo),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        write!(f, "{:?}", self.key)
+    }
+}
+
+/// Sequence of chords bound to a named action.
+#[derive(Clone, Debug)]
+pub struct Binding {
+    sequence: Vec<KeyChord>,
+    action: String,
+}
+
+impl Binding {
+    pub fn sequence(&self) -> &[KeyChord] {
+        &self.sequence
+    }
+
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, chord) in self.sequence.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+
+        Ok(())
+    }
+}
+
+type Action = Box<dyn FnMut()>;
+
+/// Bindings and the actions they run, for an app, a window or a subtree.
+pub(crate) struct Keymap {
+    bindings: Vec<Binding>,
+    actions: HashMap<String, Action>,
+    /// Chords typed so far of a longer sequence
+    pending: Vec<KeyChord>,
+    /// When the last of `pending` was typed
+    last_chord: Instant,
+}
+
+impl Keymap {
+    pub(crate) fn new() -> Self {
+        Self {
+            bindings: vec![],
+            actions: HashMap::new(),
+            pending: vec![],
+            last_chord: Instant::now(),
+        }
+    }
+
+    /// Bind `sequence` to `action`, replacing what it was bound to.
+    pub(crate) fn bind(&mut self, sequence: Vec<KeyChord>, action: &str) {
+        self.unbind(&sequence);
+        self.bindings.push(Binding {
+            sequence,
+            action: action.to_string(),
+        });
+    }
+
+    pub(crate) fn unbind(&mut self, sequence: &[KeyChord]) {
+        self.bindings
+            .retain(|binding| binding.sequence != sequence);
+    }
+
+    /// Set what `name` does, replacing what it did.
+    pub(crate) fn action(&mut self, name: &str, callback: impl FnMut() + 'static) {
+        self.actions
+            .insert(name.to_string(), Box::new(callback));
+    }
+
+    pub(crate) fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Run the action called `name`, if it's defined here.
+    pub(crate) fn run(&mut self, name: &str) -> bool {
+        match self.actions.get_mut(name) {
+            Some(callback) => {
+                callback();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handle a chord, returns whether it ran an action or continued a sequence.
+    /// Actions that aren't defined here are triggered by name.
+    pub(crate) fn handle(&mut self, chord: KeyChord) -> bool {
+        let now = Instant::now();
+        if now - self.last_chord > SEQUENCE_TIMEOUT {
+            self.pending.clear();
+        }
+        self.last_chord = now;
+        self.pending.push(chord);
+
+        loop {
+            let exact = self
+                .bindings
+                .iter()
+                .find(|binding| binding.sequence == self.pending);
+
+            if let Some(binding) = exact {
+                let action = binding.action.clone();
+                self.pending.clear();
+                if !self.run(&action) {
+                    trigger_action(&action);
+                }
+                return true;
+            }
+
+            let prefix = self.bindings.iter().any(|binding| {
+                binding
+                    .sequence
+                    .starts_with(&self.pending)
+            });
+            if prefix {
+                return true;
+            }
+
+            // A broken sequence, the chord may still start another one
+            if self.pending.len() > 1 {
+                self.pending = vec![chord];
+            } else {
+                self.pending.clear();
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use VirtualKeyCode::*;
+
+    /// Keymap with `action` counting how many times it runs.
+    fn counting(keymap: &mut Keymap, action: &str) -> Rc<Cell<usize>> {
+        let count = Rc::new(Cell::new(0));
+        let counter = count.clone();
+        keymap.action(action, move || counter.set(counter.get() + 1));
+        count
+    }
+
+    #[test]
+    fn chord_runs_its_action() {
+        let mut keymap = Keymap::new();
+        keymap.bind(KeyChord::new(S).ctrl().into(), "save");
+        let saved = counting(&mut keymap, "save");
+
+        assert!(keymap.handle(KeyChord::new(S).ctrl()));
+        assert_eq!(saved.get(), 1);
+    }
+
+    #[test]
+    fn modifiers_must_match_exactly() {
+        let mut keymap = Keymap::new();
+        keymap.bind(KeyChord::new(S).ctrl().into(), "save");
+        let saved = counting(&mut keymap, "save");
+
+        assert!(!keymap.handle(KeyChord::new(S)));
+        assert!(!keymap.handle(KeyChord::new(S).ctrl().shift()));
+        assert_eq!(saved.get(), 0);
+    }
+
+    #[test]
+    fn binding_again_replaces() {
+        let mut keymap = Keymap::new();
+        keymap.bind(KeyChord::new(S).ctrl().into(), "save");
+        keymap.bind(KeyChord::new(S).ctrl().into(), "share");
+        let saved = counting(&mut keymap, "save");
+        let shared = counting(&mut keymap, "share");
+
+        keymap.handle(KeyChord::new(S).ctrl());
+        assert_eq!((saved.get(), shared.get()), (0, 1));
+
+        keymap.unbind(&[KeyChord::new(S).ctrl()]);
+        assert!(!keymap.handle(KeyChord::new(S).ctrl()));
+    }
+
+    #[test]
+    fn sequence_runs_once_complete() {
+        let mut keymap = Keymap::new();
+        keymap.bind(
+            vec![KeyChord::new(K).ctrl(), KeyChord::new(C).ctrl()],
+            "comment",
+        );
+        let commented = counting(&mut keymap, "comment");
+
+        assert!(keymap.handle(KeyChord::new(K).ctrl()));
+        assert_eq!(commented.get(), 0);
+        assert!(keymap.handle(KeyChord::new(C).ctrl()));
+        assert_eq!(commented.get(), 1);
+
+        // Starts over once complete
+        assert!(!keymap.handle(KeyChord::new(C).ctrl()));
+    }
+
+    #[test]
+    fn broken_sequence_starts_another() {
+        let mut keymap = Keymap::new();
+        keymap.bind(
+            vec![KeyChord::new(K).ctrl(), KeyChord::new(C).ctrl()],
+            "comment",
+        );
+        keymap.bind(KeyChord::new(S).ctrl().into(), "save");
+        let commented = counting(&mut keymap, "comment");
+        let saved = counting(&mut keymap, "save");
+
+        keymap.handle(KeyChord::new(K).ctrl());
+        assert!(keymap.handle(KeyChord::new(S).ctrl()));
+        assert_eq!((commented.get(), saved.get()), (0, 1));
+
+        keymap.handle(KeyChord::new(K).ctrl());
+        assert!(!keymap.handle(KeyChord::new(X)));
+        assert!(!keymap.handle(KeyChord::new(C).ctrl()));
+        assert_eq!(commented.get(), 0);
+    }
+
+    #[test]
+    fn sequence_times_out() {
+        let mut keymap = Keymap::new();
+        keymap.bind(
+            vec![KeyChord::new(K).ctrl(), KeyChord::new(C).ctrl()],
+            "comment",
+        );
+        let commented = counting(&mut keymap, "comment");
+
+        keymap.handle(KeyChord::new(K).ctrl());
+        keymap.last_chord -= SEQUENCE_TIMEOUT * 2;
+        assert!(!keymap.handle(KeyChord::new(C).ctrl()));
+        assert_eq!(commented.get(), 0);
+    }
+
+    #[test]
+    fn undefined_actions_fall_back_to_the_app() {
+        let mut window = Keymap::new();
+        window.bind(KeyChord::new(N).ctrl().into(), "new");
+        window.bind(KeyChord::new(W).ctrl().into(), "close");
+        let closed_window = counting(&mut window, "close");
+
+        let mut app = Keymap::new();
+        let created = counting(&mut app, "new");
+        let closed_app = counting(&mut app, "close");
+
+        assert!(window.handle(KeyChord::new(N).ctrl()));
+        assert!(window.handle(KeyChord::new(W).ctrl()));
+        run_triggered_actions(&mut window, &mut app);
+
+        assert_eq!(created.get(), 1);
+        assert_eq!((closed_window.get(), closed_app.get()), (1, 0));
+    }
+
+    #[test]
+    fn chords_are_read_from_key_presses() {
+        let mut event = KeyEvent {
+            key: Some(S),
+            state: ElementState::Pressed,
+            text: None,
+            modifiers: ModifiersState::CTRL,
+            repeat: false,
+        };
+        assert_eq!(KeyChord::from_event(&event), Some(KeyChord::new(S).ctrl()));
+
+        event.state = ElementState::Released;
+        assert_eq!(KeyChord::from_event(&event), None);
+
+        event.state = ElementState::Pressed;
+        event.key = Some(LControl);
+        assert_eq!(KeyChord::from_event(&event), None);
+    }
+
+    #[test]
+    fn display() {
+        let binding = Binding {
+            sequence: vec![KeyChord::new(K).ctrl(), KeyChord::new(C).shift().ctrl()],
+            action: "comment".to_string(),
+        };
+        assert_eq!(binding.to_string(), "Ctrl+K Ctrl+Shift+C");
+    }
+}
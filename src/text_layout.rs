@@ -0,0 +1,572 @@
+use std::{cell::RefCell, ops::Range};
+
+use skia_safe::{
+    font_style::{Slant, Weight, Width},
+    textlayout::{
+        FontCollection, Paragraph, ParagraphBuilder, ParagraphStyle, RectHeightStyle,
+        RectWidthStyle, TextAlign, TextDecoration, TextDirection as SkTextDirection,
+        TextStyle as SkTextStyle, TypefaceFontProvider,
+    },
+    utils::text_utils::Align,
+    BlendMode, Canvas, Color, FontMgr, FontStyle, Paint, Rect, SaveLayerRec, Shader, TileMode,
+    Typeface,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::TextDirection;
+
+/// Family used for text the style's families don't cover, or when it names none
+const DEFAULT_FAMILY: &str = "sans-serif";
+
+/// Color emoji fonts shipped by macOS, Windows and most Linux distributions.
+/// Missing ones are skipped.
+const EMOJI_FAMILIES: [&str; 3] = ["Apple Color Emoji", "Segoe UI Emoji", "Noto Color Emoji"];
+
+thread_local! {
+    // Shared by every paragraph so glyphs and shaping results are cached across widgets
+    static FONT_COLLECTION: RefCell<FontCollection> = RefCell::new({
+        let mut font_collection = FontCollection::new();
+        // System fonts (fontconfig on Linux) are used for anything the app fonts don't cover
+        font_collection.set_default_font_manager(FontMgr::new(), None);
+        font_collection.enable_font_fallback();
+        font_collection
+    });
+
+    // Fonts registered by the app, looked up before the system ones
+    static APP_FONTS: RefCell<TypefaceFontProvider> = RefCell::new(TypefaceFontProvider::new());
+}
+
+/// Font and decorations text is laid out and painted with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    pub(crate) families: Vec<String>,
+    pub(crate) size: f32,
+    pub(crate) weight: Weight,
+    pub(crate) italic: bool,
+    pub(crate) letter_spacing: f32,
+    pub(crate) line_height: Option<f32>,
+    pub(crate) underline: bool,
+    pub(crate) strike_through: bool,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            families: vec![],
+            // Same size `Font::default()` uses
+            size: 12.0,
+            weight: Weight::NORMAL,
+            italic: false,
+            letter_spacing: 0.0,
+            line_height: None,
+            underline: false,
+            strike_through: false,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Font family to use. Call it again to add fallbacks, they are tried in order,
+    /// then color emoji fonts and the system fonts, character by character.
+    pub fn family(mut self, family: impl Into<String>) -> Self {
+        self.families.push(family.into());
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    /// Extra space added between characters.
+    pub fn letter_spacing(mut self, letter_spacing: f32) -> Self {
+        self.letter_spacing = letter_spacing;
+        self
+    }
+
+    /// Height of each line, as a multiple of the font size.
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = Some(line_height);
+        self
+    }
+
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    pub fn strike_through(mut self, strike_through: bool) -> Self {
+        self.strike_through = strike_through;
+        self
+    }
+}
+
+/// How text that doesn't fit in its area or in `max_lines` is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextOverflow {
+    /// Cut the text at the edge of its area
+    #[default]
+    Clip,
+    /// Replace the end of the last line with an ellipsis
+    Ellipsis,
+    /// Fade the last visible line out
+    Fade,
+}
+
+pub(crate) fn font_collection() -> FontCollection {
+    FONT_COLLECTION.with(|font_collection| font_collection.borrow().clone())
+}
+
+/// Read the font in `data`, `None` if it isn't a font Skia can read.
+pub(crate) fn load_font(data: &[u8]) -> Option<Typeface> {
+    FontMgr::new().new_from_data(data, None)
+}
+
+/// Make `typeface` available under `family`.
+pub(crate) fn register_font(family: &str, typeface: Typeface) {
+    APP_FONTS.with(|app_fonts| {
+        let mut app_fonts = app_fonts.borrow_mut();
+        app_fonts.register_typeface(typeface, Some(family));
+
+        FONT_COLLECTION.with(|font_collection| {
+            let mut font_collection = font_collection.borrow_mut();
+            font_collection.set_asset_font_manager(Some(app_fonts.clone().into()));
+            // Paragraphs cached so far were shaped without this font
+            font_collection.clear_caches();
+        });
+    });
+}
+
+/// Skia text style equivalent to `style`, painted in `color`.
+pub(crate) fn text_style(style: &TextStyle, color: Color) -> SkTextStyle {
+    let mut text_style = SkTextStyle::new();
+
+    text_style.set_color(color);
+    text_style.set_font_size(style.size);
+    text_style.set_letter_spacing(style.letter_spacing);
+    text_style.set_font_style(FontStyle::new(
+        style.weight,
+        Width::NORMAL,
+        if style.italic {
+            Slant::Italic
+        } else {
+            Slant::Upright
+        },
+    ));
+
+    // Emoji fonts come right after the chosen ones, otherwise the system fallback
+    // may pick a font with black and white emoji, or none at all. The default family
+    // goes before them, so text the chosen fonts lack isn't drawn with an emoji font.
+    let mut families: Vec<&str> = style
+        .families
+        .iter()
+        .map(String::as_str)
+        .collect();
+    if !families.contains(&DEFAULT_FAMILY) {
+        families.push(DEFAULT_FAMILY);
+    }
+    families.extend(EMOJI_FAMILIES);
+    text_style.set_font_families(&families);
+
+    if let Some(line_height) = style.line_height {
+        text_style.set_height(line_height);
+        text_style.set_height_override(true);
+    }
+
+    let mut decoration = TextDecoration::NO_DECORATION;
+    if style.underline {
+        decoration |= TextDecoration::UNDERLINE;
+    }
+    if style.strike_through {
+        decoration |= TextDecoration::LINE_THROUGH;
+    }
+    text_style.set_decoration_type(decoration);
+
+    text_style
+}
+
+/// Paragraph style for text that flows in the given direction.
+pub(crate) fn paragraph_style(
+    align: Align,
+    direction: TextDirection,
+    max_lines: Option<usize>,
+    overflow: TextOverflow,
+) -> ParagraphStyle {
+    let mut paragraph_style = ParagraphStyle::new();
+
+    paragraph_style.set_max_lines(max_lines);
+    if overflow == TextOverflow::Ellipsis {
+        paragraph_style.set_ellipsis("\u{2026}");
+    }
+
+    paragraph_style.set_text_align(match align {
+        Align::Left => TextAlign::Left,
+        Align::Center => TextAlign::Center,
+        Align::Right => TextAlign::Right,
+    });
+
+    paragraph_style.set_text_direction(match direction {
+        TextDirection::Ltr => SkTextDirection::LTR,
+        TextDirection::Rtl => SkTextDirection::RTL,
+    });
+
+    paragraph_style
+}
+
+/// Horizontal position of a box of `width` aligned inside the area
+/// that starts at `x` and spans `area_width`.
+pub(crate) fn align_x(align: Align, x: f32, area_width: f32, width: f32) -> f32 {
+    match align {
+        Align::Left => x,
+        Align::Center => x + (area_width - width) / 2.0,
+        Align::Right => x + area_width - width,
+    }
+}
+
+/// Wrap the paragraph at word boundaries so it fits in `max_width`.
+///
+/// Areas with no width are used as anchors (e.g. by `Center`), so text is
+/// never wrapped there.
+pub(crate) fn layout_paragraph(paragraph: &mut Paragraph, max_width: f32) {
+    paragraph.layout(f32::INFINITY);
+    let width = paragraph.max_intrinsic_width().ceil();
+
+    // Lay it out again tightly, otherwise alignment happens inside an infinite box
+    if max_width > 0.0 && max_width < width {
+        paragraph.layout(max_width);
+    } else {
+        paragraph.layout(width);
+    }
+}
+
+/// Paint a laid out paragraph at `(x, y)`, handling any overflow past `max_lines` or `max_height`.
+pub(crate) fn paint_paragraph(
+    canvas: &mut Canvas,
+    paragraph: &Paragraph,
+    (x, y): (f32, f32),
+    max_height: f32,
+    overflow: TextOverflow,
+) {
+    let overflows = paragraph.did_exceed_max_lines() || paragraph.height() > max_height;
+
+    if !overflows {
+        paragraph.paint(canvas, (x, y));
+        return;
+    }
+
+    let bounds = Rect::from_xywh(
+        x,
+        y,
+        paragraph.max_width(),
+        paragraph.height().min(max_height),
+    );
+
+    // Lines past `max_height` are cut whatever the mode, an ellipsis only
+    // shortens the last of the `max_lines`
+    canvas.save();
+    canvas.clip_rect(bounds, None, None);
+
+    match overflow {
+        TextOverflow::Fade => {
+            canvas.save_layer(&SaveLayerRec::default().bounds(&bounds));
+            paragraph.paint(canvas, (x, y));
+
+            // Fade out the last visible line
+            let line_height = paragraph
+                .get_line_metrics()
+                .iter()
+                .rev()
+                .find(|line| (line.baseline + line.descent) as f32 <= bounds.height())
+                .map(|line| (line.ascent + line.descent) as f32)
+                .unwrap_or_else(|| bounds.height());
+            let fade_top = bounds.bottom - line_height;
+
+            let mut paint = Paint::default();
+            paint.set_blend_mode(BlendMode::DstIn);
+            paint.set_shader(Shader::linear_gradient(
+                ((x, fade_top), (x, bounds.bottom)),
+                &[Color::BLACK, Color::TRANSPARENT][..],
+                None,
+                TileMode::Clamp,
+                None,
+                None,
+            ));
+            canvas.draw_rect(
+                Rect::new(bounds.left, fade_top, bounds.right, bounds.bottom),
+                &paint,
+            );
+            canvas.restore();
+        }
+        TextOverflow::Clip | TextOverflow::Ellipsis => paragraph.paint(canvas, (x, y)),
+    }
+
+    canvas.restore();
+}
+
+/// Index in UTF-16 code units, which is what Skia paragraphs use, of the byte `index` in `text`.
+pub(crate) fn utf16_index(text: &str, index: usize) -> usize {
+    text[..index]
+        .chars()
+        .map(char::len_utf16)
+        .sum()
+}
+
+/// Byte index in `text` of the UTF-16 `index` returned by a Skia paragraph.
+pub(crate) fn byte_index(text: &str, index: usize) -> usize {
+    let mut utf16 = 0;
+    for (byte, c) in text.char_indices() {
+        if utf16 >= index {
+            return byte;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Start of the grapheme before `index`.
+pub(crate) fn prev_grapheme(text: &str, index: usize) -> usize {
+    text[..index]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start)
+}
+
+/// End of the grapheme after `index`.
+pub(crate) fn next_grapheme(text: &str, index: usize) -> usize {
+    text[index..]
+        .graphemes(true)
+        .next()
+        .map_or(index, |grapheme| index + grapheme.len())
+}
+
+/// Start of the word before `index`.
+pub(crate) fn prev_word(text: &str, index: usize) -> usize {
+    text.unicode_word_indices()
+        .map(|(start, _)| start)
+        .take_while(|start| *start < index)
+        .last()
+        .unwrap_or(0)
+}
+
+/// End of the word after `index`.
+pub(crate) fn next_word(text: &str, index: usize) -> usize {
+    text.unicode_word_indices()
+        .map(|(start, word)| start + word.len())
+        .find(|end| *end > index)
+        .unwrap_or(text.len())
+}
+
+/// Word around the byte `index`, or the run of spaces or punctuation it's in.
+pub(crate) fn word_at(text: &str, index: usize) -> Range<usize> {
+    let mut last = index..index;
+    for (start, word) in text.split_word_bound_indices() {
+        let word = start..start + word.len();
+        if index < word.end {
+            return word;
+        }
+        last = word;
+    }
+
+    // At the end of the text, the last word
+    last
+}
+
+/// Caret placed before the byte `index` of `text`, as `(x, top, bottom)` in the laid out
+/// paragraph. It follows the text onto wrapped lines and into right-to-left runs.
+pub(crate) fn caret_position(paragraph: &Paragraph, text: &str, index: usize) -> (f32, f32, f32) {
+    caret_at(paragraph, text, index, utf16_index(text, index))
+}
+
+/// `caret_position` when the UTF-16 index of `index` is already known.
+fn caret_at(paragraph: &Paragraph, text: &str, index: usize, utf16: usize) -> (f32, f32, f32) {
+    let rects =
+        |range| paragraph.get_rects_for_range(range, RectHeightStyle::Max, RectWidthStyle::Tight);
+
+    // Leading edge of the next character, it's on the right line when the text wraps there
+    if let Some(c) = text[index..].chars().next() {
+        if let Some(text_box) = rects(utf16..utf16 + c.len_utf16()).first() {
+            let rect = text_box.rect;
+            let x = match text_box.direct {
+                SkTextDirection::RTL => rect.right,
+                SkTextDirection::LTR => rect.left,
+            };
+            return (x, rect.top, rect.bottom);
+        }
+    }
+
+    // Trailing edge of the last character, at the end of the text
+    if let Some(c) = text[..index].chars().next_back() {
+        if let Some(text_box) = rects(utf16 - c.len_utf16()..utf16).last() {
+            let rect = text_box.rect;
+            let x = match text_box.direct {
+                SkTextDirection::RTL => rect.left,
+                SkTextDirection::LTR => rect.right,
+            };
+            return (x, rect.top, rect.bottom);
+        }
+    }
+
+    // Empty text, where the line starts depends on the alignment
+    let x = paragraph
+        .get_line_metrics()
+        .first()
+        .map_or(0.0, |line| line.left as f32);
+    (x, 0.0, paragraph.height())
+}
+
+/// Byte index of the caret position just left of, or right of, the byte `index`,
+/// on the same line. Unlike `prev_grapheme` and `next_grapheme`, it follows the
+/// visual order, so it works across right-to-left runs.
+/// Returns `None` at the edge of the line.
+pub(crate) fn visual_neighbor(
+    paragraph: &Paragraph,
+    text: &str,
+    index: usize,
+    right: bool,
+) -> Option<usize> {
+    let utf16 = utf16_index(text, index);
+    let (x, top, _) = caret_at(paragraph, text, index, utf16);
+
+    // Only the boundaries on the caret's line can be next to it
+    let lines = paragraph.get_line_metrics();
+    let line = lines
+        .iter()
+        .find(|line| utf16 < line.end_including_newline)
+        .or(lines.last())
+        .map_or(0..=usize::MAX, |line| {
+            line.start_index..=line.end_including_newline
+        });
+
+    // Walk the text once, keeping the UTF-16 index of each boundary
+    let boundaries = text
+        .graphemes(true)
+        .chain([""])
+        .scan((0, 0), |(byte, utf16), grapheme| {
+            let boundary = (*byte, *utf16);
+            *byte += grapheme.len();
+            *utf16 += grapheme.encode_utf16().count();
+            Some(boundary)
+        });
+
+    boundaries
+        .skip_while(|(_, utf16)| *utf16 < *line.start())
+        .take_while(|(_, utf16)| line.contains(utf16))
+        .filter(|(boundary, _)| *boundary != index)
+        .filter_map(|(boundary, utf16)| {
+            let (boundary_x, boundary_top, _) = caret_at(paragraph, text, boundary, utf16);
+            let distance = if right {
+                boundary_x - x
+            } else {
+                x - boundary_x
+            };
+
+            (boundary_top == top && distance > 0.0).then_some((boundary, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(boundary, _)| boundary)
+}
+
+/// Text being composed with an input method, shown at the caret until it's committed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Preedit {
+    pub(crate) text: String,
+    /// Byte index of the input method's cursor in `text`, `None` when it should be hidden
+    pub(crate) cursor: Option<usize>,
+}
+
+impl Preedit {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// `text` with the preedit inserted at the byte `index`.
+    pub(crate) fn compose(&self, text: &str, index: usize) -> String {
+        let mut composed = text.to_string();
+        composed.insert_str(index, &self.text);
+        composed
+    }
+}
+
+/// Add `text` to the paragraph, underlining the bytes in `composing`,
+/// i.e. text still being composed with an input method.
+pub(crate) fn add_composing_text(
+    builder: &mut ParagraphBuilder,
+    text_style: &SkTextStyle,
+    text: &str,
+    composing: Option<Range<usize>>,
+) {
+    let composing = match composing {
+        Some(composing) => composing,
+        None => {
+            builder.add_text(text);
+            return;
+        }
+    };
+
+    let mut underlined = text_style.clone();
+    underlined.set_decoration_type(TextDecoration::UNDERLINE);
+
+    builder.add_text(&text[..composing.start]);
+    builder.push_style(&underlined);
+    builder.add_text(&text[composing.clone()]);
+    builder.pop();
+    builder.add_text(&text[composing.end..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1, 2 and 4 bytes long, the last one 2 UTF-16 units
+    const TEXT: &str = "a\u{e9}\u{1F600}b";
+
+    #[test]
+    fn utf16_index_counts_surrogate_pairs() {
+        assert_eq!(utf16_index(TEXT, 0), 0);
+        assert_eq!(utf16_index(TEXT, 1), 1);
+        assert_eq!(utf16_index(TEXT, 3), 2);
+        assert_eq!(utf16_index(TEXT, 7), 4);
+        assert_eq!(utf16_index(TEXT, TEXT.len()), 5);
+    }
+
+    #[test]
+    fn byte_index_is_the_inverse_of_utf16_index() {
+        for (byte, _) in TEXT.char_indices() {
+            assert_eq!(byte_index(TEXT, utf16_index(TEXT, byte)), byte);
+        }
+        assert_eq!(byte_index(TEXT, 5), TEXT.len());
+    }
+
+    #[test]
+    fn word_at_finds_the_word_around() {
+        let text = "hello, wide world";
+        assert_eq!(word_at(text, 0), 0..5);
+        assert_eq!(word_at(text, 3), 0..5);
+        assert_eq!(word_at(text, 5), 5..6);
+        assert_eq!(word_at(text, 6), 6..7);
+        assert_eq!(word_at(text, 9), 7..11);
+        assert_eq!(word_at(text, text.len()), 12..17);
+        assert_eq!(word_at("", 0), 0..0);
+    }
+
+    #[test]
+    fn byte_index_past_the_end_is_the_end() {
+        assert_eq!(byte_index(TEXT, 100), TEXT.len());
+        assert_eq!(byte_index("", 0), 0);
+    }
+}
@@ -1,17 +1,72 @@
+/// Intrinsic sizes and baseline of a widget laid out exactly like its `child`, an
+/// `Option<Box<dyn Widget>>`, to put in its `impl Widget`.
+macro_rules! forward_intrinsics {
+    () => {
+        fn min_intrinsic_width(&self, ctx: $crate::Context) -> f32 {
+            self.child
+                .as_ref()
+                .map_or(0.0, |child| child.min_intrinsic_width(ctx))
+        }
+
+        fn max_intrinsic_width(&self, ctx: $crate::Context) -> f32 {
+            self.child
+                .as_ref()
+                .map_or(0.0, |child| child.max_intrinsic_width(ctx))
+        }
+
+        fn min_intrinsic_height(&self, ctx: $crate::Context) -> f32 {
+            self.child
+                .as_ref()
+                .map_or(0.0, |child| child.min_intrinsic_height(ctx))
+        }
+
+        fn max_intrinsic_height(&self, ctx: $crate::Context) -> f32 {
+            self.child
+                .as_ref()
+                .map_or(0.0, |child| child.max_intrinsic_height(ctx))
+        }
+
+        fn baseline(&self, ctx: $crate::Context) -> Option<f32> {
+            self.child.as_ref()?.baseline(ctx)
+        }
+    };
+}
+
 mod button;
 mod center;
+mod directionality;
+mod draggable;
+mod drop_target;
 mod expand;
+mod gesture_detector;
+mod intrinsic_width;
+mod layout_builder;
 mod list;
 mod padding;
+mod rich_text;
+mod shortcuts;
 mod stateful;
 mod text;
+mod text_area;
+mod text_input;
+mod text_selection;
 mod triangle;
 
 pub use button::*;
 pub use center::*;
+pub use directionality::*;
+pub use draggable::*;
+pub use drop_target::*;
 pub use expand::*;
+pub use gesture_detector::*;
+pub use intrinsic_width::*;
+pub use layout_builder::*;
 pub use list::*;
 pub use padding::*;
+pub use rich_text::*;
+pub use shortcuts::*;
 pub use stateful::*;
 pub use text::*;
+pub use text_area::*;
+pub use text_input::*;
 pub use triangle::*;
@@ -1,10 +1,21 @@
-use glutin::event::MouseButton;
-use skia_safe::{Canvas, Color, Paint, PaintStyle, Path};
+use glutin::{
+    event::{ElementState, MouseButton, VirtualKeyCode},
+    window::CursorIcon,
+};
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Path, Rect};
 
-use crate::{Context, Widget, YalemEvent, YalemMouse};
+use crate::{
+    dispatch, gestures, Context, FocusId, KeyEvent, TextDirection, Widget, YalemEvent, YalemMouse,
+};
 
 pub struct Button {
     background_color: Color,
+    hover_background_color: Option<Color>,
+    hovered: bool,
+    /// Whether the current press started on the button
+    pressed: bool,
+    member: usize,
+    focus_id: FocusId,
     child: Option<Box<dyn Widget>>,
     width: Option<f32>,
     height: Option<f32>,
@@ -16,10 +27,55 @@ impl Button {
     pub fn builder() -> ButtonBuilder {
         ButtonBuilder::new()
     }
+
+    /// Run the callback, returns whether there is one.
+    fn click(&mut self) -> bool {
+        match &mut self.callback {
+            Some(callback) => {
+                callback();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Left, top, right and bottom edges of the button drawn in `ctx`.
+    fn edges(&self, ctx: &Context) -> (f32, f32, f32, f32) {
+        let mut x = ctx.x;
+        let y = ctx.y;
+        let mut width = ctx.width;
+        let mut height = ctx.height;
+
+        if let Some(child) = &self.child {
+            let size = child.get_size(ctx.clone());
+            width = ctx.x + size.0;
+            height = ctx.y + size.1;
+        }
+
+        if let Some(w) = self.width {
+            width = ctx.x + w;
+        }
+
+        if let Some(h) = self.height {
+            height = ctx.y + h;
+        }
+
+        // Mirror the button so it sticks to the right edge
+        if ctx.direction == TextDirection::Rtl {
+            let right = ctx.x + ctx.width;
+            let w = width - ctx.x;
+            x = right - w;
+            width = right;
+        }
+
+        (x, y, width, height)
+    }
 }
 
 pub struct ButtonBuilder {
     background_color: Color,
+    hover_background_color: Option<Color>,
+    focus_id: Option<FocusId>,
     child: Option<Box<dyn Widget>>,
     width: Option<f32>,
     height: Option<f32>,
@@ -30,6 +86,8 @@ impl ButtonBuilder {
     pub fn new() -> Self {
         Self {
             background_color: Color::TRANSPARENT,
+            hover_background_color: None,
+            focus_id: None,
             child: None,
             width: None,
             height: None,
@@ -61,6 +119,18 @@ impl ButtonBuilder {
         self
     }
 
+    /// Background while the pointer is over the button.
+    pub fn hover_background(mut self, color: Color) -> Self {
+        self.hover_background_color = Some(color);
+        self
+    }
+
+    /// Id to move the focus to the button with `request_focus`.
+    pub fn focus_id(mut self, id: FocusId) -> Self {
+        self.focus_id = Some(id);
+        self
+    }
+
     pub fn on_click<T>(mut self, callback: T) -> Self
     where
         T: FnMut() -> () + 'static,
@@ -74,6 +144,13 @@ impl From<ButtonBuilder> for Button {
     fn from(button_builder: ButtonBuilder) -> Self {
         Self {
             background_color: button_builder.background_color,
+            hover_background_color: button_builder.hover_background_color,
+            hovered: false,
+            pressed: false,
+            member: gestures::new_member_id(),
+            focus_id: button_builder
+                .focus_id
+                .unwrap_or_default(),
             child: button_builder.child,
             width: button_builder.width,
             height: button_builder.height,
@@ -84,29 +161,97 @@ impl From<ButtonBuilder> for Button {
 }
 
 impl Widget for Button {
-    fn send_event(&mut self, event: &YalemEvent) {
-        if let Some(child) = &mut self.child {
-            child.send_event(&event)
-        }
-
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        // It clicks when a press on it is released over it, unless a gesture of another
+        // widget, like a drag, won the press first. Presses aren't handled so those can
+        // see them.
         match event {
-            YalemEvent::YalemMouse(YalemMouse::Pressed { button, position }) => {
-                if let Some(callback) = &mut self.callback {
-                    if &MouseButton::Left == button {
-                        if position.0 >= self.positions.0
-                            && position.1 >= self.positions.1
-                            && position.0 <= self.positions.2
-                            && position.1 <= self.positions.3
-                        {
-                            callback()
-                        }
-                    }
-                }
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                button: MouseButton::Left,
+                ..
+            }) => {
+                dispatch::take_focus(self.focus_id);
+                self.pressed = true;
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Released {
+                pointer,
+                button: MouseButton::Left,
+                position,
+                ..
+            }) if self.pressed => {
+                self.pressed = false;
+                self.hit_test(*position)
+                    && self.callback.is_some()
+                    && gestures::claim(*pointer, self.member)
+                    && self.click()
+            }
+            YalemEvent::YalemMouse(YalemMouse::Cancelled { .. }) => {
+                self.pressed = false;
+                false
             }
-            _ => {}
+            YalemEvent::Key(KeyEvent {
+                key:
+                    Some(
+                        VirtualKeyCode::Return
+                        | VirtualKeyCode::NumpadEnter
+                        | VirtualKeyCode::Space,
+                    ),
+                state: ElementState::Pressed,
+                repeat: false,
+                ..
+            }) if self.focus_id.is_focused() => self.click(),
+            YalemEvent::YalemMouse(YalemMouse::Entered { .. }) => {
+                self.hovered = true;
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Exited { .. }) => {
+                self.hovered = false;
+                false
+            }
+            _ => false,
         }
     }
 
+    fn focus_id(&self) -> Option<FocusId> {
+        Some(self.focus_id)
+    }
+
+    // Buttons that do nothing are skipped by Tab
+    fn in_tab_order(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        self.callback
+            .as_ref()
+            .map(|_| CursorIcon::Hand)
+    }
+
+    fn focus_ring(&self) -> Option<Rect> {
+        let (left, top, right, bottom) = self.positions;
+        Some(Rect::new(
+            left as f32,
+            top as f32,
+            right as f32,
+            bottom as f32,
+        ))
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        position.0 >= self.positions.0
+            && position.1 >= self.positions.1
+            && position.0 <= self.positions.2
+            && position.1 <= self.positions.3
+    }
+
     fn get_size(&self, ctx: Context) -> (f32, f32) {
         let mut height = ctx.height;
         let mut width = ctx.width;
@@ -128,32 +273,54 @@ impl Widget for Button {
         (width, height)
     }
 
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        match (self.width, &self.child) {
+            (Some(w), _) => w,
+            (None, Some(child)) => child.min_intrinsic_width(ctx),
+            (None, None) => 0.0,
+        }
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        match (self.width, &self.child) {
+            (Some(w), _) => w,
+            (None, Some(child)) => child.max_intrinsic_width(ctx),
+            (None, None) => 0.0,
+        }
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        match (self.height, &self.child) {
+            (Some(h), _) => h,
+            (None, Some(child)) => child.min_intrinsic_height(ctx),
+            (None, None) => 0.0,
+        }
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        match (self.height, &self.child) {
+            (Some(h), _) => h,
+            (None, Some(child)) => child.max_intrinsic_height(ctx),
+            (None, None) => 0.0,
+        }
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.child.as_ref()?.baseline(ctx)
+    }
+
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
         let mut path = Path::new();
         let mut paint = Paint::default();
 
         paint.set_anti_alias(true);
         paint.set_style(PaintStyle::Fill);
-        paint.set_color(self.background_color);
+        paint.set_color(match self.hover_background_color {
+            Some(color) if self.hovered => color,
+            _ => self.background_color,
+        });
 
-        let x = ctx.x;
-        let y = ctx.y;
-        let mut width = ctx.width;
-        let mut height = ctx.height;
-
-        if let Some(child) = &self.child {
-            let size = child.get_size(ctx.clone());
-            width = ctx.x + size.0;
-            height = ctx.y + size.1;
-        }
-
-        if let Some(w) = self.width {
-            width = ctx.x + w;
-        }
-
-        if let Some(h) = self.height {
-            height = ctx.y + h;
-        }
+        let (x, y, width, height) = self.edges(&ctx);
 
         path.move_to((x, y));
         path.line_to((width, y));
@@ -165,8 +332,48 @@ impl Widget for Button {
         path.close();
         canvas.draw_path(&path, &paint);
 
+        // The child moves with the button when it's mirrored
         if let Some(child) = &mut self.child {
-            child.draw(canvas, ctx);
+            child.draw(
+                canvas,
+                Context {
+                    x,
+                    width: ctx.x + ctx.width - x,
+                    ..ctx
+                },
+            );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(direction: TextDirection) -> Context {
+        Context {
+            x: 30.0,
+            y: 10.0,
+            width: 200.0,
+            height: 100.0,
+            direction,
+        }
+    }
+
+    #[test]
+    fn rtl_buttons_stick_to_the_right_edge() {
+        let button = Button::builder()
+            .width(40.0)
+            .height(20.0)
+            .build();
+
+        assert_eq!(
+            button.edges(&context(TextDirection::Ltr)),
+            (30.0, 10.0, 70.0, 30.0)
+        );
+        assert_eq!(
+            button.edges(&context(TextDirection::Rtl)),
+            (190.0, 10.0, 230.0, 30.0)
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use skia_safe::Canvas;
 
-use crate::{widgets::*, Context, Widget, YalemEvent};
+use crate::{widgets::*, Context, Widget};
 
 pub enum Direction {
     Horizontal,
@@ -32,6 +32,7 @@ impl CenterBuilder {
                 right: 0.0,
                 bottom: 0.0,
                 top: 0.0,
+                directional: false,
                 child: None,
             }),
             direction: Direction::Horizontal,
@@ -63,14 +64,34 @@ impl From<CenterBuilder> for Center {
 }
 
 impl Widget for Center {
-    fn send_event(&mut self, event: &YalemEvent) {
-        self.child.send_event(event);
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        vec![self.child.as_mut()]
     }
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
         self.child.get_size(ctx)
     }
 
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.child.min_intrinsic_width(ctx)
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.child.max_intrinsic_width(ctx)
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.child.min_intrinsic_height(ctx)
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.child.max_intrinsic_height(ctx)
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.child.baseline(ctx)
+    }
+
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
         match self.direction {
             Direction::Horizontal => {
@@ -0,0 +1,126 @@
+use skia_safe::Canvas;
+
+use crate::{Context, TextDirection, Widget};
+
+/// Overrides the text direction inherited by its child.
+pub struct Directionality {
+    child: Option<Box<dyn Widget>>,
+    direction: TextDirection,
+}
+
+impl Directionality {
+    pub fn builder(direction: TextDirection) -> DirectionalityBuilder {
+        DirectionalityBuilder::new(direction)
+    }
+}
+
+pub struct DirectionalityBuilder {
+    child: Option<Box<dyn Widget>>,
+    direction: TextDirection,
+}
+
+impl DirectionalityBuilder {
+    pub fn new(direction: TextDirection) -> Self {
+        Self {
+            child: None,
+            direction,
+        }
+    }
+
+    pub fn build(self) -> Directionality {
+        Directionality::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+}
+
+impl From<DirectionalityBuilder> for Directionality {
+    fn from(directionality_builder: DirectionalityBuilder) -> Self {
+        Self {
+            child: directionality_builder.child,
+            direction: directionality_builder.direction,
+        }
+    }
+}
+
+impl Widget for Directionality {
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        if let Some(child) = &self.child {
+            child.get_size(Context {
+                direction: self.direction,
+                ..ctx
+            })
+        } else {
+            (ctx.width, ctx.height)
+        }
+    }
+
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        let ctx = Context {
+            direction: self.direction,
+            ..ctx
+        };
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.min_intrinsic_width(ctx))
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        let ctx = Context {
+            direction: self.direction,
+            ..ctx
+        };
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.max_intrinsic_width(ctx))
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        let ctx = Context {
+            direction: self.direction,
+            ..ctx
+        };
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.min_intrinsic_height(ctx))
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        let ctx = Context {
+            direction: self.direction,
+            ..ctx
+        };
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.max_intrinsic_height(ctx))
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.child.as_ref()?.baseline(Context {
+            direction: self.direction,
+            ..ctx
+        })
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        if let Some(child) = &mut self.child {
+            child.draw(
+                canvas,
+                Context {
+                    direction: self.direction,
+                    ..ctx
+                },
+            )
+        }
+    }
+}
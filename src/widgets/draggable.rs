@@ -0,0 +1,206 @@
+use glutin::{event::MouseButton, window::CursorIcon};
+use skia_safe::{Canvas, Rect};
+
+use super::gesture_detector::DRAG_SLOP;
+use crate::{
+    capture_pointer, drag, gestures, rect_contains, Context, PointerId, Widget, YalemEvent,
+    YalemMouse,
+};
+
+type Feedback = Box<dyn Fn() -> Box<dyn Widget>>;
+
+/// Lets its child be dragged onto a `DropTarget` taking `T`, which gets a copy of `data`.
+pub struct Draggable<T> {
+    data: T,
+    child: Option<Box<dyn Widget>>,
+    feedback: Option<Feedback>,
+    on_drag_start: Option<Box<dyn FnMut()>>,
+    member: usize,
+    area: Rect,
+    /// Pointer pressed on the widget and where
+    press: Option<(PointerId, (f64, f64))>,
+    dragging: bool,
+}
+
+impl<T: Clone + 'static> Draggable<T> {
+    pub fn builder(data: T) -> DraggableBuilder<T> {
+        DraggableBuilder::new(data)
+    }
+
+    fn is_pressed(&self, pointer: PointerId) -> bool {
+        self.press
+            .is_some_and(|(pressed, _)| pressed == pointer)
+    }
+
+    /// End the press, and the drag with `end_drag` if there's one. Returns whether
+    /// there was.
+    fn end(&mut self, end_drag: impl FnOnce()) -> bool {
+        self.press = None;
+        if !self.dragging {
+            return false;
+        }
+
+        self.dragging = false;
+        end_drag();
+        true
+    }
+
+    fn on_move(&mut self, pointer: PointerId, position: (f64, f64)) {
+        let start = match self.press {
+            Some((pressed, start)) if pressed == pointer => start,
+            _ => return,
+        };
+
+        if self.dragging {
+            drag::move_to(position);
+            return;
+        }
+
+        if gestures::lost(pointer, self.member) {
+            self.press = None;
+            return;
+        }
+
+        let moved = (position.0 - start.0).hypot(position.1 - start.1);
+        if moved > DRAG_SLOP && gestures::claim(pointer, self.member) {
+            self.dragging = true;
+            capture_pointer(pointer);
+            drag::start(
+                Box::new(self.data.clone()),
+                Some(pointer),
+                position,
+                self.feedback
+                    .as_ref()
+                    .map(|feedback| feedback()),
+            );
+
+            if let Some(on_drag_start) = &mut self.on_drag_start {
+                on_drag_start();
+            }
+        }
+    }
+}
+
+pub struct DraggableBuilder<T> {
+    data: T,
+    child: Option<Box<dyn Widget>>,
+    feedback: Option<Feedback>,
+    on_drag_start: Option<Box<dyn FnMut()>>,
+}
+
+impl<T: Clone + 'static> DraggableBuilder<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            data,
+            child: None,
+            feedback: None,
+            on_drag_start: None,
+        }
+    }
+
+    pub fn build(self) -> Draggable<T> {
+        Draggable::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Widget drawn under the pointer while dragging, built when the drag starts.
+    pub fn feedback<W: Widget + 'static>(mut self, feedback: impl Fn() -> W + 'static) -> Self {
+        self.feedback = Some(Box::new(move || Box::new(feedback())));
+        self
+    }
+
+    pub fn on_drag_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.on_drag_start = Some(Box::new(callback));
+        self
+    }
+}
+
+impl<T: Clone + 'static> From<DraggableBuilder<T>> for Draggable<T> {
+    fn from(draggable_builder: DraggableBuilder<T>) -> Self {
+        Self {
+            data: draggable_builder.data,
+            child: draggable_builder.child,
+            feedback: draggable_builder.feedback,
+            on_drag_start: draggable_builder.on_drag_start,
+            member: gestures::new_member_id(),
+            area: Rect::default(),
+            press: None,
+            dragging: false,
+        }
+    }
+}
+
+impl<T: Clone + 'static> Widget for Draggable<T> {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match event {
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                pointer,
+                button: MouseButton::Left,
+                position,
+                ..
+            }) if self.press.is_none() => {
+                self.press = Some((*pointer, *position));
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Moved {
+                pointer, position, ..
+            }) => {
+                self.on_move(*pointer, *position);
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Released {
+                pointer,
+                button: MouseButton::Left,
+                position,
+                ..
+            }) if self.is_pressed(*pointer) => self.end(|| drag::drop_at(*position)),
+            YalemEvent::YalemMouse(YalemMouse::Cancelled { pointer, .. })
+                if self.is_pressed(*pointer) =>
+            {
+                self.end(drag::cancel)
+            }
+            _ => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(if self.dragging {
+            CursorIcon::Grabbing
+        } else {
+            CursorIcon::Grab
+        })
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        rect_contains(&self.area, position)
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        match &self.child {
+            Some(child) => child.get_size(ctx),
+            None => (ctx.width, ctx.height),
+        }
+    }
+
+    forward_intrinsics!();
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let (width, height) = self.get_size(ctx.clone());
+        self.area = Rect::from_xywh(ctx.x, ctx.y, width, height);
+
+        if let Some(child) = &mut self.child {
+            child.draw(canvas, ctx)
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::marker::PhantomData;
+
+use glutin::window::CursorIcon;
+use skia_safe::{Canvas, Color, Paint, PaintStyle, Rect};
+
+use crate::{rect_contains, Context, Widget, YalemEvent};
+
+type WillAccept<T> = Box<dyn FnMut(&T) -> bool>;
+type OnAccept<T> = Box<dyn FnMut(&T)>;
+
+/// Takes what is dropped on its child if it's a `T`, from a `Draggable<T>`, or a
+/// `Vec<PathBuf>` of the files dropped from another app.
+pub struct DropTarget<T> {
+    child: Option<Box<dyn Widget>>,
+    will_accept: Option<WillAccept<T>>,
+    on_accept: Option<OnAccept<T>>,
+    accept_color: Option<Color>,
+    reject_color: Option<Color>,
+    area: Rect,
+    /// Whether the payload dragged over the widget would be accepted
+    hovered: Option<bool>,
+    payload: PhantomData<T>,
+}
+
+impl<T: 'static> DropTarget<T> {
+    pub fn builder() -> DropTargetBuilder<T> {
+        DropTargetBuilder::new()
+    }
+
+    fn will_accept(&mut self, payload: &T) -> bool {
+        match &mut self.will_accept {
+            Some(will_accept) => will_accept(payload),
+            None => true,
+        }
+    }
+}
+
+pub struct DropTargetBuilder<T> {
+    child: Option<Box<dyn Widget>>,
+    will_accept: Option<WillAccept<T>>,
+    on_accept: Option<OnAccept<T>>,
+    accept_color: Option<Color>,
+    reject_color: Option<Color>,
+}
+
+impl<T: 'static> DropTargetBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            child: None,
+            will_accept: None,
+            on_accept: None,
+            accept_color: None,
+            reject_color: None,
+        }
+    }
+
+    pub fn build(self) -> DropTarget<T> {
+        DropTarget::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Whether to take the payload, asked while it's dragged over the widget and when
+    /// it's dropped. Everything is taken by default.
+    pub fn will_accept(mut self, callback: impl FnMut(&T) -> bool + 'static) -> Self {
+        self.will_accept = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_accept(mut self, callback: impl FnMut(&T) + 'static) -> Self {
+        self.on_accept = Some(Box::new(callback));
+        self
+    }
+
+    /// Background while a payload it would take is dragged over the widget.
+    pub fn accept_color(mut self, color: Color) -> Self {
+        self.accept_color = Some(color);
+        self
+    }
+
+    /// Background while a payload it wouldn't take is dragged over the widget.
+    pub fn reject_color(mut self, color: Color) -> Self {
+        self.reject_color = Some(color);
+        self
+    }
+}
+
+impl<T: 'static> From<DropTargetBuilder<T>> for DropTarget<T> {
+    fn from(drop_target_builder: DropTargetBuilder<T>) -> Self {
+        Self {
+            child: drop_target_builder.child,
+            will_accept: drop_target_builder.will_accept,
+            on_accept: drop_target_builder.on_accept,
+            accept_color: drop_target_builder.accept_color,
+            reject_color: drop_target_builder.reject_color,
+            area: Rect::default(),
+            hovered: None,
+            payload: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static> Widget for DropTarget<T> {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        // Payloads of other types are left to the drop targets around this one
+        match event {
+            YalemEvent::DragOver { payload, .. } => match payload.downcast_ref::<T>() {
+                Some(payload) => {
+                    self.hovered = Some(self.will_accept(payload));
+                    true
+                }
+                None => false,
+            },
+            YalemEvent::Drop { payload, .. } => match payload.downcast_ref::<T>() {
+                Some(payload) => {
+                    if self.will_accept(payload) {
+                        if let Some(on_accept) = &mut self.on_accept {
+                            on_accept(payload);
+                        }
+                    }
+                    true
+                }
+                None => false,
+            },
+            YalemEvent::DragLeft => {
+                self.hovered = None;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        match self.hovered {
+            Some(false) => Some(CursorIcon::NotAllowed),
+            _ => None,
+        }
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        rect_contains(&self.area, position)
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        match &self.child {
+            Some(child) => child.get_size(ctx),
+            None => (ctx.width, ctx.height),
+        }
+    }
+
+    forward_intrinsics!();
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let (width, height) = self.get_size(ctx.clone());
+        self.area = Rect::from_xywh(ctx.x, ctx.y, width, height);
+
+        let color = match self.hovered {
+            Some(true) => self.accept_color,
+            Some(false) => self.reject_color,
+            None => None,
+        };
+        if let Some(color) = color {
+            let mut paint = Paint::default();
+            paint.set_style(PaintStyle::Fill);
+            paint.set_color(color);
+            canvas.draw_rect(self.area, &paint);
+        }
+
+        if let Some(child) = &mut self.child {
+            child.draw(canvas, ctx)
+        }
+    }
+}
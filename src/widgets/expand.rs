@@ -1,6 +1,6 @@
 use skia_safe::Canvas;
 
-use crate::{widgets::*, Context, Widget, YalemEvent};
+use crate::{widgets::*, Context, Widget};
 
 pub struct Expand {
     child: Option<Box<dyn Widget>>,
@@ -51,10 +51,11 @@ impl From<ExpandBuilder> for Expand {
 }
 
 impl Widget for Expand {
-    fn send_event(&mut self, event: &YalemEvent) {
-        if let Some(child) = &mut self.child {
-            child.send_event(&event)
-        }
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
     }
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
@@ -82,6 +83,8 @@ impl Widget for Expand {
         }
     }
 
+    forward_intrinsics!();
+
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
         if let Some(child) = &mut self.child {
             let mut width = ctx.width;
@@ -0,0 +1,655 @@
+use std::{
+    collections::VecDeque,
+    f64::consts::{PI, TAU},
+    time::{Duration, Instant},
+};
+
+use glutin::event::MouseButton;
+use skia_safe::{Canvas, Rect};
+
+use crate::{
+    capture_pointer, gestures, rect_contains, request_redraw_at, Context, PointerId, Widget,
+    YalemEvent, YalemMouse,
+};
+
+/// How far the pointer moves before a drag starts, and a tap or long press is cancelled
+pub(crate) const DRAG_SLOP: f64 = 8.0;
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+const DOUBLE_TAP_INTERVAL: Duration = Duration::from_millis(300);
+/// Pointer positions older than this don't count towards the velocity of a drag
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug)]
+pub struct DragStart {
+    /// Where the pointer was pressed
+    pub position: (f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct DragUpdate {
+    pub position: (f64, f64),
+    /// Movement since the last update
+    pub delta: (f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct DragEnd {
+    pub position: (f64, f64),
+    /// In pixels per second, when the pointer was released
+    pub velocity: (f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct ScaleStart {
+    /// Point halfway between the two fingers
+    pub focal_point: (f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct ScaleUpdate {
+    pub focal_point: (f64, f64),
+    /// Distance between the fingers over what it was at the start
+    pub scale: f64,
+    /// In radians, clockwise, since the start
+    pub rotation: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScaleEnd {
+    pub scale: f64,
+    pub rotation: f64,
+}
+
+type Callback = Box<dyn FnMut()>;
+type DragCallback<T> = Box<dyn FnMut(&T)>;
+
+/// Press being tracked.
+struct Press {
+    pointer: PointerId,
+    start: (f64, f64),
+    position: (f64, f64),
+    long_press_at: Instant,
+    /// Whether the pointer stayed within the slop, so it can still be a tap or long press
+    still: bool,
+    dragging: bool,
+    long_pressed: bool,
+    samples: VecDeque<(Instant, (f64, f64))>,
+}
+
+/// Two finger gesture being tracked.
+struct Scale {
+    pointers: [PointerId; 2],
+    positions: [(f64, f64); 2],
+    start_distance: f64,
+    /// Angle of the line between the fingers at the last update
+    angle: f64,
+    scale: f64,
+    rotation: f64,
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn angle(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (b.1 - a.1).atan2(b.0 - a.0)
+}
+
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// Recognizes taps, double taps, long presses and drags on its child, and scaling and
+/// rotating it with two fingers.
+///
+/// Only the gestures with a callback are recognized. When recognizers of nested
+/// widgets could use the same press, the first gesture recognized wins it.
+pub struct GestureDetector {
+    child: Option<Box<dyn Widget>>,
+    on_tap: Option<Callback>,
+    on_double_tap: Option<Callback>,
+    on_long_press: Option<Callback>,
+    on_drag_start: Option<DragCallback<DragStart>>,
+    on_drag_update: Option<DragCallback<DragUpdate>>,
+    on_drag_end: Option<DragCallback<DragEnd>>,
+    on_scale_start: Option<DragCallback<ScaleStart>>,
+    on_scale_update: Option<DragCallback<ScaleUpdate>>,
+    on_scale_end: Option<DragCallback<ScaleEnd>>,
+    double_tap_interval: Duration,
+    member: usize,
+    area: Rect,
+    press: Option<Press>,
+    scale: Option<Scale>,
+    /// Tap waiting to see if a second one makes it a double tap
+    pending_tap: Option<(Instant, (f64, f64))>,
+}
+
+impl GestureDetector {
+    pub fn builder() -> GestureDetectorBuilder {
+        GestureDetectorBuilder::new()
+    }
+
+    fn recognizes_drag(&self) -> bool {
+        self.on_drag_start.is_some() || self.on_drag_update.is_some() || self.on_drag_end.is_some()
+    }
+
+    fn recognizes_scale(&self) -> bool {
+        self.on_scale_start.is_some()
+            || self.on_scale_update.is_some()
+            || self.on_scale_end.is_some()
+    }
+
+    fn recognizes_any(&self) -> bool {
+        self.on_tap.is_some()
+            || self.on_double_tap.is_some()
+            || self.on_long_press.is_some()
+            || self.recognizes_drag()
+            || self.recognizes_scale()
+    }
+
+    fn tap(&mut self) {
+        if let Some(on_tap) = &mut self.on_tap {
+            on_tap();
+        }
+    }
+
+    /// Fire the pending tap once no double tap can follow it.
+    fn flush_pending_tap(&mut self, now: Instant) {
+        if let Some((time, _)) = self.pending_tap {
+            if now - time >= self.double_tap_interval {
+                self.pending_tap = None;
+                self.tap();
+            }
+        }
+    }
+
+    fn on_press(&mut self, pointer: PointerId, position: (f64, f64)) {
+        let now = Instant::now();
+        self.flush_pending_tap(now);
+
+        if self.scale.is_some() {
+            return;
+        }
+        // Another finger while one is down
+        if let Some(press) = &self.press {
+            if press.pointer != pointer {
+                if self.recognizes_scale() {
+                    self.start_scale(pointer, position);
+                }
+                return;
+            }
+        }
+
+        self.press = Some(Press {
+            pointer,
+            start: position,
+            position,
+            long_press_at: now + LONG_PRESS_DURATION,
+            still: true,
+            dragging: false,
+            long_pressed: false,
+            samples: VecDeque::from([(now, position)]),
+        });
+
+        if self.on_long_press.is_some() {
+            request_redraw_at(now + LONG_PRESS_DURATION);
+        }
+    }
+
+    /// Turn the press into a scale with the finger `pointer` just pressed, if this wins
+    /// both fingers.
+    fn start_scale(&mut self, pointer: PointerId, position: (f64, f64)) {
+        let first = self.press.as_ref().unwrap();
+        if !gestures::claim(first.pointer, self.member) || !gestures::claim(pointer, self.member) {
+            return;
+        }
+
+        let press = self.press.take().unwrap();
+        if press.dragging {
+            self.end_drag(&press);
+        }
+
+        capture_pointer(press.pointer);
+        capture_pointer(pointer);
+
+        let positions = [press.position, position];
+        self.scale = Some(Scale {
+            pointers: [press.pointer, pointer],
+            positions,
+            start_distance: distance(positions[0], positions[1]),
+            angle: angle(positions[0], positions[1]),
+            scale: 1.0,
+            rotation: 0.0,
+        });
+
+        if let Some(on_scale_start) = &mut self.on_scale_start {
+            on_scale_start(&ScaleStart {
+                focal_point: midpoint(positions[0], positions[1]),
+            });
+        }
+    }
+
+    /// Returns whether `pointer` is one of the fingers of the scale.
+    fn move_scale(&mut self, pointer: PointerId, position: (f64, f64)) -> bool {
+        let scale = match &mut self.scale {
+            Some(scale) => scale,
+            None => return false,
+        };
+        let finger = match scale
+            .pointers
+            .iter()
+            .position(|other| *other == pointer)
+        {
+            Some(finger) => finger,
+            None => return false,
+        };
+        scale.positions[finger] = position;
+
+        let [a, b] = scale.positions;
+        if scale.start_distance > 0.0 {
+            scale.scale = distance(a, b) / scale.start_distance;
+        }
+        // Add up the turns since the last update, so it keeps going past half a turn
+        let angle = angle(a, b);
+        let mut turn = angle - scale.angle;
+        if turn > PI {
+            turn -= TAU;
+        } else if turn < -PI {
+            turn += TAU;
+        }
+        scale.rotation += turn;
+        scale.angle = angle;
+
+        if let Some(on_scale_update) = &mut self.on_scale_update {
+            on_scale_update(&ScaleUpdate {
+                focal_point: midpoint(a, b),
+                scale: scale.scale,
+                rotation: scale.rotation,
+            });
+        }
+        true
+    }
+
+    fn on_move(&mut self, pointer: PointerId, position: (f64, f64)) {
+        if self.move_scale(pointer, position) {
+            return;
+        }
+
+        let recognizes_drag = self.recognizes_drag();
+        let press = match &mut self.press {
+            Some(press) if press.pointer == pointer => press,
+            _ => return,
+        };
+
+        if gestures::lost(pointer, self.member) {
+            self.press = None;
+            return;
+        }
+
+        let now = Instant::now();
+        let delta = (position.0 - press.position.0, position.1 - press.position.1);
+        press.position = position;
+        press.samples.push_back((now, position));
+        while press
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now - *time > VELOCITY_WINDOW)
+        {
+            press.samples.pop_front();
+        }
+
+        if press.dragging {
+            if let Some(on_drag_update) = &mut self.on_drag_update {
+                on_drag_update(&DragUpdate { position, delta });
+            }
+            return;
+        }
+
+        if distance(press.start, position) <= DRAG_SLOP {
+            return;
+        }
+        press.still = false;
+
+        if recognizes_drag && gestures::claim(pointer, self.member) {
+            press.dragging = true;
+            capture_pointer(pointer);
+
+            if let Some(on_drag_start) = &mut self.on_drag_start {
+                on_drag_start(&DragStart {
+                    position: press.start,
+                });
+            }
+            if let Some(on_drag_update) = &mut self.on_drag_update {
+                on_drag_update(&DragUpdate {
+                    position,
+                    delta: (position.0 - press.start.0, position.1 - press.start.1),
+                });
+            }
+        }
+    }
+
+    fn end_drag(&mut self, press: &Press) {
+        let now = Instant::now();
+        let samples: Vec<_> = press
+            .samples
+            .iter()
+            .filter(|(time, _)| now - *time <= VELOCITY_WINDOW)
+            .collect();
+        let velocity = match (samples.first(), samples.last()) {
+            (Some((first_time, first)), Some((last_time, last))) if last_time > first_time => {
+                let elapsed = (*last_time - *first_time).as_secs_f64();
+                ((last.0 - first.0) / elapsed, (last.1 - first.1) / elapsed)
+            }
+            _ => (0.0, 0.0),
+        };
+
+        if let Some(on_drag_end) = &mut self.on_drag_end {
+            on_drag_end(&DragEnd {
+                position: press.position,
+                velocity,
+            });
+        }
+    }
+
+    /// End the press of `pointer`, `inside` is whether it was released over the widget.
+    /// Returns whether it ended a gesture.
+    fn on_release(&mut self, pointer: PointerId, inside: bool) -> bool {
+        // Lifting either finger ends a scale, the other one is then ignored
+        if let Some(scale) = &self.scale {
+            if !scale.pointers.contains(&pointer) {
+                return false;
+            }
+            let scale = self.scale.take().unwrap();
+            if let Some(on_scale_end) = &mut self.on_scale_end {
+                on_scale_end(&ScaleEnd {
+                    scale: scale.scale,
+                    rotation: scale.rotation,
+                });
+            }
+            return true;
+        }
+
+        let press = match self.press.take() {
+            Some(press) if press.pointer == pointer => press,
+            press => {
+                self.press = press;
+                return false;
+            }
+        };
+        let now = Instant::now();
+
+        if press.dragging {
+            self.end_drag(&press);
+            return true;
+        }
+
+        if !inside || !press.still || press.long_pressed || gestures::lost(pointer, self.member) {
+            return false;
+        }
+
+        if self.on_double_tap.is_some() {
+            let double_tap = self
+                .pending_tap
+                .take()
+                .is_some_and(|(time, position)| {
+                    now - time < self.double_tap_interval
+                        && distance(position, press.start) <= DRAG_SLOP
+                });
+
+            if !gestures::claim(pointer, self.member) {
+                return false;
+            }
+
+            if double_tap {
+                if let Some(on_double_tap) = &mut self.on_double_tap {
+                    on_double_tap();
+                }
+            } else {
+                // The tap fires once it's clear no second one follows
+                self.pending_tap = Some((now, press.start));
+                request_redraw_at(now + self.double_tap_interval);
+            }
+            true
+        } else if self.on_tap.is_some() && gestures::claim(pointer, self.member) {
+            self.tap();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn on_wake(&mut self) {
+        let now = Instant::now();
+        self.flush_pending_tap(now);
+
+        if let Some(press) = &mut self.press {
+            if press.still
+                && !press.long_pressed
+                && now >= press.long_press_at
+                && self.on_long_press.is_some()
+                && gestures::claim(press.pointer, self.member)
+            {
+                press.long_pressed = true;
+                if let Some(on_long_press) = &mut self.on_long_press {
+                    on_long_press();
+                }
+            }
+        }
+    }
+}
+
+pub struct GestureDetectorBuilder {
+    child: Option<Box<dyn Widget>>,
+    on_tap: Option<Callback>,
+    on_double_tap: Option<Callback>,
+    on_long_press: Option<Callback>,
+    on_drag_start: Option<DragCallback<DragStart>>,
+    on_drag_update: Option<DragCallback<DragUpdate>>,
+    on_drag_end: Option<DragCallback<DragEnd>>,
+    on_scale_start: Option<DragCallback<ScaleStart>>,
+    on_scale_update: Option<DragCallback<ScaleUpdate>>,
+    on_scale_end: Option<DragCallback<ScaleEnd>>,
+    double_tap_interval: Duration,
+}
+
+impl GestureDetectorBuilder {
+    pub fn new() -> Self {
+        Self {
+            child: None,
+            on_tap: None,
+            on_double_tap: None,
+            on_long_press: None,
+            on_drag_start: None,
+            on_drag_update: None,
+            on_drag_end: None,
+            on_scale_start: None,
+            on_scale_update: None,
+            on_scale_end: None,
+            double_tap_interval: DOUBLE_TAP_INTERVAL,
+        }
+    }
+
+    pub fn build(self) -> GestureDetector {
+        GestureDetector::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Called when the pointer is pressed and released without moving. With a double tap
+    /// callback too, it's only called once the double tap interval is over.
+    pub fn on_tap(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.on_tap = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_double_tap(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.on_double_tap = Some(Box::new(callback));
+        self
+    }
+
+    /// Longest time between two taps of a double tap.
+    pub fn double_tap_interval(mut self, interval: Duration) -> Self {
+        self.double_tap_interval = interval;
+        self
+    }
+
+    pub fn on_long_press(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.on_long_press = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_drag_start(mut self, callback: impl FnMut(&DragStart) + 'static) -> Self {
+        self.on_drag_start = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_drag_update(mut self, callback: impl FnMut(&DragUpdate) + 'static) -> Self {
+        self.on_drag_update = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_drag_end(mut self, callback: impl FnMut(&DragEnd) + 'static) -> Self {
+        self.on_drag_end = Some(Box::new(callback));
+        self
+    }
+
+    /// Called when a second finger touches the widget while the first one is still on it.
+    pub fn on_scale_start(mut self, callback: impl FnMut(&ScaleStart) + 'static) -> Self {
+        self.on_scale_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Called as the two fingers move, with how far apart and how turned they are
+    /// compared to the start.
+    pub fn on_scale_update(mut self, callback: impl FnMut(&ScaleUpdate) + 'static) -> Self {
+        self.on_scale_update = Some(Box::new(callback));
+        self
+    }
+
+    /// Called when one of the two fingers is lifted.
+    pub fn on_scale_end(mut self, callback: impl FnMut(&ScaleEnd) + 'static) -> Self {
+        self.on_scale_end = Some(Box::new(callback));
+        self
+    }
+}
+
+impl From<GestureDetectorBuilder> for GestureDetector {
+    fn from(gesture_detector_builder: GestureDetectorBuilder) -> Self {
+        Self {
+            child: gesture_detector_builder.child,
+            on_tap: gesture_detector_builder.on_tap,
+            on_double_tap: gesture_detector_builder.on_double_tap,
+            on_long_press: gesture_detector_builder.on_long_press,
+            on_drag_start: gesture_detector_builder.on_drag_start,
+            on_drag_update: gesture_detector_builder.on_drag_update,
+            on_drag_end: gesture_detector_builder.on_drag_end,
+            on_scale_start: gesture_detector_builder.on_scale_start,
+            on_scale_update: gesture_detector_builder.on_scale_update,
+            on_scale_end: gesture_detector_builder.on_scale_end,
+            double_tap_interval: gesture_detector_builder.double_tap_interval,
+            member: gestures::new_member_id(),
+            area: Rect::default(),
+            press: None,
+            scale: None,
+            pending_tap: None,
+        }
+    }
+}
+
+impl Widget for GestureDetector {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match event {
+            // Presses aren't handled, so the recognizers of the ancestors get them too
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                pointer,
+                button: MouseButton::Left,
+                position,
+                ..
+            }) if self.recognizes_any() => {
+                self.on_press(*pointer, *position);
+                false
+            }
+            // Moves and releases outside of the widget still end up here, since the press
+            // was on it
+            YalemEvent::YalemMouse(YalemMouse::Moved {
+                pointer, position, ..
+            }) => {
+                self.on_move(*pointer, *position);
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Released {
+                pointer,
+                button: MouseButton::Left,
+                position,
+                ..
+            }) => self.on_release(*pointer, rect_contains(&self.area, *position)),
+            YalemEvent::YalemMouse(YalemMouse::Cancelled { pointer, .. }) => {
+                self.on_release(*pointer, false)
+            }
+            YalemEvent::Wake => {
+                self.on_wake();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        self.recognizes_any() && rect_contains(&self.area, position)
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        match &self.child {
+            Some(child) => child.get_size(ctx),
+            None => (ctx.width, ctx.height),
+        }
+    }
+
+    forward_intrinsics!();
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let (width, height) = self.get_size(ctx.clone());
+        self.area = Rect::from_xywh(ctx.x, ctx.y, width, height);
+
+        if let Some(child) = &mut self.child {
+            child.draw(canvas, ctx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn pending_tap_fires_at_the_double_tap_interval() {
+        let taps = Rc::new(Cell::new(0));
+        let counter = taps.clone();
+        let mut gesture_detector = GestureDetector::builder()
+            .on_tap(move || counter.set(counter.get() + 1))
+            .on_double_tap(|| {})
+            .build();
+
+        let now = Instant::now();
+        gesture_detector.pending_tap = Some((now, (0.0, 0.0)));
+        gesture_detector.flush_pending_tap(now + DOUBLE_TAP_INTERVAL / 2);
+        assert_eq!(taps.get(), 0);
+
+        // The wake is scheduled exactly then
+        gesture_detector.flush_pending_tap(now + DOUBLE_TAP_INTERVAL);
+        assert_eq!(taps.get(), 1);
+        assert!(gesture_detector.pending_tap.is_none());
+    }
+}
@@ -0,0 +1,106 @@
+use skia_safe::Canvas;
+
+use crate::{Context, Widget};
+
+/// Shrinks its child to the width it would take with no constraints, e.g. so a button
+/// in a `List` is as wide as its label instead of the whole list.
+pub struct IntrinsicWidth {
+    child: Option<Box<dyn Widget>>,
+}
+
+impl IntrinsicWidth {
+    pub fn builder() -> IntrinsicWidthBuilder {
+        IntrinsicWidthBuilder::new()
+    }
+
+    /// `ctx` narrowed to the child's intrinsic width.
+    fn child_context(child: &dyn Widget, ctx: Context) -> Context {
+        let width = child
+            .max_intrinsic_width(ctx.clone())
+            .min(ctx.width);
+        Context { width, ..ctx }
+    }
+}
+
+pub struct IntrinsicWidthBuilder {
+    child: Option<Box<dyn Widget>>,
+}
+
+impl IntrinsicWidthBuilder {
+    pub fn new() -> Self {
+        Self { child: None }
+    }
+
+    pub fn build(self) -> IntrinsicWidth {
+        IntrinsicWidth::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+}
+
+impl From<IntrinsicWidthBuilder> for IntrinsicWidth {
+    fn from(intrinsic_width_builder: IntrinsicWidthBuilder) -> Self {
+        Self {
+            child: intrinsic_width_builder.child,
+        }
+    }
+}
+
+impl Widget for IntrinsicWidth {
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        match &self.child {
+            Some(child) => child.get_size(Self::child_context(child.as_ref(), ctx)),
+            None => (0.0, 0.0),
+        }
+    }
+
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.min_intrinsic_width(ctx))
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| child.max_intrinsic_width(ctx))
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| {
+                child.min_intrinsic_height(Self::child_context(child.as_ref(), ctx))
+            })
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.child
+            .as_ref()
+            .map_or(0.0, |child| {
+                child.max_intrinsic_height(Self::child_context(child.as_ref(), ctx))
+            })
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        let child = self.child.as_ref()?;
+        child.baseline(Self::child_context(child.as_ref(), ctx))
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        if let Some(child) = &mut self.child {
+            let ctx = Self::child_context(child.as_ref(), ctx);
+            child.draw(canvas, ctx)
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use skia_safe::Canvas;
+
+use crate::{Context, TextDirection, Widget};
+
+type Builder = dyn Fn(&Context) -> Box<dyn Widget>;
+
+/// Width, height and direction a child was built for.
+type Constraints = (f32, f32, TextDirection);
+
+fn constraints(ctx: &Context) -> Constraints {
+    (ctx.width, ctx.height, ctx.direction)
+}
+
+/// Builds its child from the space it is given, e.g. to switch between a
+/// compact and a wide layout depending on the window width. The child is only
+/// built again when the width, height or direction change.
+pub struct LayoutBuilder {
+    builder: Box<Builder>,
+    /// Child built in the last draw, kept until it's drawn with other constraints
+    child: Option<(Constraints, Box<dyn Widget>)>,
+}
+
+impl LayoutBuilder {
+    pub fn new<T>(builder: T) -> Self
+    where
+        T: Fn(&Context) -> Box<dyn Widget> + 'static,
+    {
+        Self {
+            builder: Box::new(builder),
+            child: None,
+        }
+    }
+
+    /// Run `f` with the child for `ctx`. Queries for other constraints than the drawn
+    /// child's get a child of their own, so they don't replace the one on screen.
+    fn with_child<T>(&self, ctx: &Context, f: impl FnOnce(&dyn Widget) -> T) -> T {
+        match &self.child {
+            Some((built_for, child)) if *built_for == constraints(ctx) => f(child.as_ref()),
+            _ => f((self.builder)(ctx).as_ref()),
+        }
+    }
+}
+
+impl Widget for LayoutBuilder {
+    // Events go to the child built in the last draw, which is the one on screen
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|(_, child)| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        self.with_child(&ctx, |child| child.get_size(ctx.clone()))
+    }
+
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.with_child(&ctx, |child| child.min_intrinsic_width(ctx.clone()))
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.with_child(&ctx, |child| child.max_intrinsic_width(ctx.clone()))
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.with_child(&ctx, |child| child.min_intrinsic_height(ctx.clone()))
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.with_child(&ctx, |child| child.max_intrinsic_height(ctx.clone()))
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.with_child(&ctx, |child| child.baseline(ctx.clone()))
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let built_for = constraints(&ctx);
+        let (_, child) = match &mut self.child {
+            Some(child) if child.0 == built_for => child,
+            child => child.insert((built_for, (self.builder)(&ctx))),
+        };
+        child.draw(canvas, ctx);
+    }
+}
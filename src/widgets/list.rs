@@ -1,6 +1,6 @@
 use skia_safe::Canvas;
 
-use crate::{Context, Widget, YalemEvent};
+use crate::{Context, Widget};
 
 pub struct List {
     children: Vec<Box<dyn Widget>>,
@@ -40,10 +40,11 @@ impl From<ListBuilder> for List {
 }
 
 impl Widget for List {
-    fn send_event(&mut self, event: &YalemEvent) {
-        for child in &mut self.children {
-            child.send_event(event);
-        }
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.children
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
     }
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
@@ -65,6 +66,38 @@ impl Widget for List {
         (ctx.width, prev_pos_y)
     }
 
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.children
+            .iter()
+            .map(|child| child.min_intrinsic_width(ctx.clone()))
+            .fold(0.0, f32::max)
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.children
+            .iter()
+            .map(|child| child.max_intrinsic_width(ctx.clone()))
+            .fold(0.0, f32::max)
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.children
+            .iter()
+            .map(|child| child.min_intrinsic_height(ctx.clone()))
+            .sum()
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.children
+            .iter()
+            .map(|child| child.max_intrinsic_height(ctx.clone()))
+            .sum()
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.children.first()?.baseline(ctx)
+    }
+
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
         let mut prev_pos_y = ctx.y;
         let max_pos = ctx.y + ctx.height;
@@ -79,6 +112,7 @@ impl Widget for List {
                     y: prev_pos_y,
                     width: ctx.width,
                     height: height_left,
+                    direction: ctx.direction,
                 },
             );
             prev_pos_y += child
@@ -87,6 +121,7 @@ impl Widget for List {
                     y: prev_pos_y,
                     width: ctx.width,
                     height: height_left,
+                    direction: ctx.direction,
                 })
                 .1;
         }
@@ -1,12 +1,13 @@
 use skia_safe::Canvas;
 
-use crate::{Context, Widget, YalemEvent};
+use crate::{Context, TextDirection, Widget};
 
 pub struct Padding {
     pub(crate) left: f32,
     pub(crate) right: f32,
     pub(crate) bottom: f32,
     pub(crate) top: f32,
+    pub(crate) directional: bool,
     pub(crate) child: Option<Box<dyn Widget>>,
 }
 
@@ -14,6 +15,44 @@ impl Padding {
     pub fn builder(positions: (f32, f32, f32, f32)) -> PaddingBuilder {
         PaddingBuilder::new(positions)
     }
+
+    /// Like `builder`, but the horizontal paddings are `start` and `end`,
+    /// so they are mirrored in right-to-left layouts.
+    pub fn directional(positions: (f32, f32, f32, f32)) -> PaddingBuilder {
+        PaddingBuilder::directional(positions)
+    }
+
+    /// Left and right paddings, resolved for the given direction.
+    fn horizontal(&self, direction: TextDirection) -> (f32, f32) {
+        if self.directional && direction == TextDirection::Rtl {
+            (self.right, self.left)
+        } else {
+            (self.left, self.right)
+        }
+    }
+
+    /// Area left to the child once the paddings are applied.
+    fn child_context(&self, ctx: Context) -> Context {
+        let (left, right) = self.horizontal(ctx.direction);
+        let mut width = ctx.width - right - left;
+        let mut height = ctx.height - self.top - self.bottom;
+
+        if height > ctx.height {
+            height = ctx.height;
+        }
+
+        if width > ctx.width {
+            width = ctx.width;
+        }
+
+        Context {
+            x: ctx.x + left,
+            y: ctx.y + self.top,
+            width,
+            height,
+            ..ctx
+        }
+    }
 }
 
 pub struct PaddingBuilder {
@@ -21,6 +60,7 @@ pub struct PaddingBuilder {
     right: f32,
     bottom: f32,
     top: f32,
+    directional: bool,
     child: Option<Box<dyn Widget>>,
 }
 
@@ -31,10 +71,18 @@ impl PaddingBuilder {
             right,
             bottom,
             top,
+            directional: false,
             child: None,
         }
     }
 
+    pub fn directional((start, end, top, bottom): (f32, f32, f32, f32)) -> Self {
+        Self {
+            directional: true,
+            ..Self::new((start, end, top, bottom))
+        }
+    }
+
     pub fn build(self) -> Padding {
         Padding::from(self)
     }
@@ -52,66 +100,82 @@ impl From<PaddingBuilder> for Padding {
             right: padding_builder.right,
             bottom: padding_builder.bottom,
             top: padding_builder.top,
+            directional: padding_builder.directional,
             child: padding_builder.child,
         }
     }
 }
 
 impl Widget for Padding {
-    fn send_event(&mut self, event: &YalemEvent) {
-        if let Some(child) = &mut self.child {
-            child.send_event(&event)
-        }
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
     }
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
         let child = self.child.as_ref().unwrap();
-        let mut width = ctx.width - self.right - self.left;
-        let mut height = ctx.height - self.top - self.bottom;
-
-        if height > ctx.height {
-            height = ctx.height;
-        }
-
-        if width > ctx.width {
-            width = ctx.width;
-        }
-
-        let child_size = child.get_size(Context {
-            x: ctx.x + self.left,
-            y: ctx.y + self.top,
-            width,
-            height,
-        });
+        let child_size = child.get_size(self.child_context(ctx.clone()));
 
         (
-            child_size.0 + self.right + self.left,
+            child_size.0 + self.left + self.right,
             child_size.1 + self.top + self.bottom,
         )
     }
 
-    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
-        let mut width = ctx.width - self.right - self.left;
-        let mut height = ctx.height - self.top - self.bottom;
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        let child_ctx = self.child_context(ctx);
+        let child_size = self
+            .child
+            .as_ref()
+            .map_or(0.0, |child| child.min_intrinsic_width(child_ctx));
 
-        if height > ctx.height {
-            height = ctx.height;
-        }
+        child_size + self.left + self.right
+    }
 
-        if width > ctx.width {
-            width = ctx.width;
-        }
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        let child_ctx = self.child_context(ctx);
+        let child_size = self
+            .child
+            .as_ref()
+            .map_or(0.0, |child| child.max_intrinsic_width(child_ctx));
+
+        child_size + self.left + self.right
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        let child_ctx = self.child_context(ctx);
+        let child_size = self
+            .child
+            .as_ref()
+            .map_or(0.0, |child| child.min_intrinsic_height(child_ctx));
+
+        child_size + self.top + self.bottom
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        let child_ctx = self.child_context(ctx);
+        let child_size = self
+            .child
+            .as_ref()
+            .map_or(0.0, |child| child.max_intrinsic_height(child_ctx));
+
+        child_size + self.top + self.bottom
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        let child = self.child.as_ref()?;
+        child
+            .baseline(self.child_context(ctx))
+            .map(|baseline| baseline + self.top)
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let child_ctx = self.child_context(ctx);
 
         if let Some(child) = &mut self.child {
-            child.draw(
-                canvas,
-                Context {
-                    x: ctx.x + self.left,
-                    y: ctx.y + self.top,
-                    width,
-                    height,
-                },
-            )
+            child.draw(canvas, child_ctx)
         }
     }
 }
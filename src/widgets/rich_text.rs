@@ -0,0 +1,340 @@
+use skia_safe::{
+    textlayout::{
+        Paragraph, ParagraphBuilder, PlaceholderAlignment, PlaceholderStyle, TextBaseline,
+    },
+    Canvas, Color, Rect,
+};
+
+use super::text_selection::TextSelection;
+use crate::{
+    text_layout::{
+        align_x, font_collection, layout_paragraph, paint_paragraph, paragraph_style, text_style,
+        TextOverflow, TextStyle,
+    },
+    Context, FocusId, TextAlign, Widget, YalemEvent,
+};
+
+/// Piece of a `RichText` paragraph.
+pub enum InlineSpan {
+    Text(TextSpan),
+    /// Widget laid out inline, sitting on the text baseline
+    Widget(Box<dyn Widget>),
+}
+
+/// Styled run of text. Children are appended after the text and inherit
+/// the color and style unless they set their own.
+pub struct TextSpan {
+    text: String,
+    color: Option<Color>,
+    style: Option<TextStyle>,
+    children: Vec<InlineSpan>,
+}
+
+impl TextSpan {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+            style: None,
+            children: vec![],
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn child(mut self, span: TextSpan) -> Self {
+        self.children
+            .push(InlineSpan::Text(span));
+        self
+    }
+
+    pub fn widget(mut self, widget: impl Widget + 'static) -> Self {
+        self.children
+            .push(InlineSpan::Widget(Box::new(widget)));
+        self
+    }
+
+    fn build(
+        &self,
+        builder: &mut ParagraphBuilder,
+        ctx: &Context,
+        (color, style): (Color, &TextStyle),
+    ) {
+        let color = self.color.unwrap_or(color);
+        let style = self.style.as_ref().unwrap_or(style);
+
+        builder.push_style(&text_style(style, color));
+        builder.add_text(&self.text);
+
+        for child in &self.children {
+            match child {
+                InlineSpan::Text(span) => span.build(builder, ctx, (color, style)),
+                InlineSpan::Widget(widget) => {
+                    let width = widget
+                        .max_intrinsic_width(ctx.clone())
+                        .min(ctx.width);
+                    let height = widget
+                        .max_intrinsic_height(Context {
+                            width,
+                            ..ctx.clone()
+                        })
+                        .min(ctx.height);
+                    let baseline = widget
+                        .baseline(ctx.clone())
+                        .unwrap_or(height);
+
+                    builder.add_placeholder(&PlaceholderStyle::new(
+                        width,
+                        height,
+                        PlaceholderAlignment::Baseline,
+                        TextBaseline::Alphabetic,
+                        baseline,
+                    ));
+                }
+            }
+        }
+
+        builder.pop();
+    }
+
+    /// Text as the paragraph sees it, with a placeholder character for each widget.
+    fn plain_text(&self, text: &mut String) {
+        text.push_str(&self.text);
+
+        for child in &self.children {
+            match child {
+                InlineSpan::Text(span) => span.plain_text(text),
+                InlineSpan::Widget(_) => text.push('\u{FFFC}'),
+            }
+        }
+    }
+
+    /// Inline widgets, in the same order their placeholders were added.
+    fn widgets_mut<'a>(&'a mut self, widgets: &mut Vec<&'a mut Box<dyn Widget>>) {
+        for child in &mut self.children {
+            match child {
+                InlineSpan::Text(span) => span.widgets_mut(widgets),
+                InlineSpan::Widget(widget) => widgets.push(widget),
+            }
+        }
+    }
+}
+
+/// Paragraph made of differently styled spans and inline widgets.
+pub struct RichText {
+    span: TextSpan,
+    align: TextAlign,
+    max_lines: Option<usize>,
+    overflow: TextOverflow,
+    selection: Option<TextSelection>,
+}
+
+impl RichText {
+    pub fn builder(span: TextSpan) -> RichTextBuilder {
+        RichTextBuilder::new(span)
+    }
+
+    fn paragraph(&self, ctx: &Context) -> Paragraph {
+        let paragraph_style = paragraph_style(
+            self.align.resolve(ctx.direction),
+            ctx.direction,
+            self.max_lines,
+            self.overflow,
+        );
+
+        let mut builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+        self.span
+            .build(&mut builder, ctx, (Color::BLACK, &TextStyle::default()));
+
+        let mut paragraph = builder.build();
+        layout_paragraph(&mut paragraph, ctx.width);
+        paragraph
+    }
+}
+
+pub struct RichTextBuilder {
+    span: TextSpan,
+    align: TextAlign,
+    max_lines: Option<usize>,
+    overflow: TextOverflow,
+    selectable: bool,
+}
+
+impl RichTextBuilder {
+    pub fn new(span: TextSpan) -> Self {
+        Self {
+            span,
+            align: TextAlign::Start,
+            max_lines: None,
+            overflow: TextOverflow::Clip,
+            selectable: false,
+        }
+    }
+
+    pub fn build(self) -> RichText {
+        RichText::from(self)
+    }
+
+    /// Defaults to `TextAlign::Start`, the start of the inherited text direction.
+    pub fn align(mut self, align: impl Into<TextAlign>) -> Self {
+        self.align = align.into();
+        self
+    }
+
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Let the text be selected with the mouse and copied.
+    pub fn selectable(mut self, selectable: bool) -> Self {
+        self.selectable = selectable;
+        self
+    }
+}
+
+impl From<RichTextBuilder> for RichText {
+    fn from(rich_text_builder: RichTextBuilder) -> Self {
+        Self {
+            span: rich_text_builder.span,
+            align: rich_text_builder.align,
+            max_lines: rich_text_builder.max_lines,
+            overflow: rich_text_builder.overflow,
+            selection: rich_text_builder
+                .selectable
+                .then(TextSelection::new),
+        }
+    }
+}
+
+impl Widget for RichText {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match &mut self.selection {
+            Some(selection) => selection.send_event(event, || {
+                let mut text = String::new();
+                self.span.plain_text(&mut text);
+                text
+            }),
+            None => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        let mut widgets = vec![];
+        self.span.widgets_mut(&mut widgets);
+
+        widgets
+            .into_iter()
+            .map(|widget| widget.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        self.selection
+            .as_ref()
+            .is_some_and(|selection| selection.hit_test(position))
+    }
+
+    // Only takes the focus to copy the selection
+    fn focus_id(&self) -> Option<FocusId> {
+        self.selection
+            .as_ref()
+            .map(TextSelection::focus_id)
+    }
+
+    fn in_tab_order(&self) -> bool {
+        false
+    }
+
+    fn focus_ring(&self) -> Option<Rect> {
+        None
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        let paragraph = self.paragraph(&ctx);
+        let mut width = paragraph.max_width();
+        let height = paragraph.height();
+
+        if width > ctx.width {
+            width = ctx.width;
+        }
+
+        (width, height)
+    }
+
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.paragraph(&ctx)
+            .min_intrinsic_width()
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.paragraph(&ctx)
+            .max_intrinsic_width()
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.paragraph(&ctx).height()
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.paragraph(&ctx).height()
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        Some(
+            self.paragraph(&ctx)
+                .alphabetic_baseline(),
+        )
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let paragraph = self.paragraph(&ctx);
+        let x = align_x(
+            self.align.resolve(ctx.direction),
+            ctx.x,
+            ctx.width,
+            paragraph.max_width(),
+        );
+
+        let paragraph = match &mut self.selection {
+            Some(selection) => selection.paint(canvas, paragraph, (x, ctx.y)),
+            None => &paragraph,
+        };
+
+        paint_paragraph(canvas, paragraph, (x, ctx.y), ctx.height, self.overflow);
+
+        let mut widgets = vec![];
+        self.span.widgets_mut(&mut widgets);
+
+        // Placeholders past `max_lines` have no box, so those widgets are not drawn
+        for (widget, placeholder) in widgets
+            .into_iter()
+            .zip(paragraph.get_rects_for_placeholders())
+        {
+            let rect = placeholder.rect;
+            widget.draw(
+                canvas,
+                Context {
+                    x: x + rect.left,
+                    y: ctx.y + rect.top,
+                    width: rect.width(),
+                    height: rect.height(),
+                    ..ctx
+                },
+            );
+        }
+    }
+}
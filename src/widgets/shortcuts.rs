@@ -0,0 +1,99 @@
+use skia_safe::Canvas;
+
+use crate::{shortcuts::Keymap, Binding, Context, KeyChord, Widget, YalemEvent};
+
+/// Shortcuts that only work while the focus is inside its child. They come before
+/// the ones of the enclosing `Shortcuts`, the window and the app.
+pub struct Shortcuts {
+    keymap: Keymap,
+    child: Option<Box<dyn Widget>>,
+}
+
+impl Shortcuts {
+    pub fn builder() -> ShortcutsBuilder {
+        ShortcutsBuilder::new()
+    }
+
+    /// Bindings, e.g. to list them in a help overlay.
+    pub fn bindings(&self) -> &[Binding] {
+        self.keymap.bindings()
+    }
+}
+
+pub struct ShortcutsBuilder {
+    keymap: Keymap,
+    child: Option<Box<dyn Widget>>,
+}
+
+impl ShortcutsBuilder {
+    pub fn new() -> Self {
+        Self {
+            keymap: Keymap::new(),
+            child: None,
+        }
+    }
+
+    pub fn build(self) -> Shortcuts {
+        Shortcuts::from(self)
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    /// Run `action` when `sequence` is typed. Actions not defined with `action` are
+    /// looked up in the window and the app.
+    pub fn bind(mut self, sequence: impl Into<Vec<KeyChord>>, action: &str) -> Self {
+        self.keymap
+            .bind(sequence.into(), action);
+        self
+    }
+
+    pub fn action(mut self, name: &str, callback: impl FnMut() + 'static) -> Self {
+        self.keymap.action(name, callback);
+        self
+    }
+}
+
+impl From<ShortcutsBuilder> for Shortcuts {
+    fn from(shortcuts_builder: ShortcutsBuilder) -> Self {
+        Self {
+            keymap: shortcuts_builder.keymap,
+            child: shortcuts_builder.child,
+        }
+    }
+}
+
+impl Widget for Shortcuts {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match event {
+            YalemEvent::Key(key) => {
+                KeyChord::from_event(key).is_some_and(|chord| self.keymap.handle(chord))
+            }
+            _ => false,
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        match &self.child {
+            Some(child) => child.get_size(ctx),
+            None => (0.0, 0.0),
+        }
+    }
+
+    forward_intrinsics!();
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        if let Some(child) = &mut self.child {
+            child.draw(canvas, ctx)
+        }
+    }
+}
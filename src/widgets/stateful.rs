@@ -1,6 +1,6 @@
 use skia_safe::Canvas;
 
-use crate::{Context, Widget, YalemEvent};
+use crate::{Context, Widget};
 
 pub struct StateContext {}
 
@@ -13,6 +13,8 @@ impl StateContext {
 pub struct Stateful {
     render: fn(&StateContext) -> Box<dyn Widget>,
     state_ctx: StateContext,
+    /// Child rendered in the last draw
+    child: Option<Box<dyn Widget>>,
 }
 
 impl Stateful {
@@ -20,21 +22,98 @@ impl Stateful {
         Self {
             render,
             state_ctx: StateContext::new(),
+            child: None,
+        }
+    }
+
+    /// Run `f` with the child rendered in the last draw, or a new one before the first.
+    fn with_child<T>(&self, f: impl FnOnce(&dyn Widget) -> T) -> T {
+        match &self.child {
+            Some(child) => f(child.as_ref()),
+            None => f((self.render)(&self.state_ctx).as_ref()),
         }
     }
 }
 
 impl Widget for Stateful {
-    fn send_event(&mut self, event: &YalemEvent) {
-        let mut child = (self.render)(&self.state_ctx);
-        child.send_event(&event);
+    fn children_mut(&mut self) -> Vec<&mut dyn Widget> {
+        self.child
+            .iter_mut()
+            .map(|child| child.as_mut() as &mut dyn Widget)
+            .collect()
     }
 
     fn get_size(&self, ctx: Context) -> (f32, f32) {
-        (self.render)(&self.state_ctx).get_size(ctx)
+        self.with_child(|child| child.get_size(ctx))
+    }
+
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.with_child(|child| child.min_intrinsic_width(ctx))
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.with_child(|child| child.max_intrinsic_width(ctx))
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.with_child(|child| child.min_intrinsic_height(ctx))
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.with_child(|child| child.max_intrinsic_height(ctx))
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        self.with_child(|child| child.baseline(ctx))
     }
 
     fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
-        (self.render)(&self.state_ctx).draw(canvas, ctx)
+        let child = self
+            .child
+            .insert((self.render)(&self.state_ctx));
+        child.draw(canvas, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::TextDirection;
+
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Sized((f32, f32));
+
+    impl Widget for Sized {
+        fn get_size(&self, _ctx: Context) -> (f32, f32) {
+            self.0
+        }
+
+        fn draw(&mut self, _canvas: &mut Canvas, _ctx: Context) {}
+    }
+
+    fn render(_state_ctx: &StateContext) -> Box<dyn Widget> {
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+        Box::new(Sized((10.0, 20.0)))
+    }
+
+    #[test]
+    fn measures_the_drawn_child() {
+        let ctx = Context {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+            direction: TextDirection::Ltr,
+        };
+        let mut stateful = Stateful::new(render);
+        assert_eq!(stateful.get_size(ctx.clone()), (10.0, 20.0));
+
+        stateful.child = Some(Box::new(Sized((30.0, 40.0))));
+        let renders = RENDERS.load(Ordering::SeqCst);
+        assert_eq!(stateful.get_size(ctx), (30.0, 40.0));
+        assert_eq!(RENDERS.load(Ordering::SeqCst), renders);
     }
 }
@@ -1,31 +1,121 @@
-use skia_safe::{utils::text_utils::Align, Canvas, Color, Font, Paint, PaintStyle};
+use std::ops::Range;
 
-use crate::{Context, Widget};
+use skia_safe::{
+    textlayout::{Paragraph, ParagraphBuilder},
+    Canvas, Color, Rect,
+};
+
+use super::text_selection::TextSelection;
+pub use crate::text_layout::{TextOverflow, TextStyle};
+use crate::{
+    text_layout::{
+        align_x, byte_index, font_collection, layout_paragraph, paint_paragraph, paragraph_style,
+        text_style,
+    },
+    Context, FocusId, TextAlign, TextDirection, Widget, YalemEvent,
+};
+
+/// Size of a piece of text once laid out, see `Text::measure`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub height: f32,
+    /// Distance from the top to the baseline of the first line
+    pub baseline: f32,
+    pub line_count: usize,
+    /// Byte range of the text on each line
+    pub lines: Vec<Range<usize>>,
+}
+
+/// Measure `text` laid out left to right with `style` only, see `Text::measure` to
+/// also set the alignment, direction or the number of lines.
+pub fn measure_text(text: &str, style: &TextStyle, max_width: f32) -> TextMetrics {
+    Text::builder(text)
+        .style(style.clone())
+        .build()
+        .measure(TextDirection::Ltr, max_width)
+}
 
 pub struct Text {
     color: Color,
+    style: TextStyle,
     text: String,
-    align: Align,
+    align: TextAlign,
+    max_lines: Option<usize>,
+    overflow: TextOverflow,
+    selection: Option<TextSelection>,
 }
 
 impl Text {
     pub fn builder(text: impl Into<String>) -> TextBuilder {
         TextBuilder::new(text)
     }
+
+    /// Lay out the text the way it's drawn in `direction`, wrapping it to fit in
+    /// `max_width`, and measure it. A `max_width` of 0 means no wrapping.
+    pub fn measure(&self, direction: TextDirection, max_width: f32) -> TextMetrics {
+        let paragraph = self.paragraph(direction, max_width);
+
+        // Skia counts in UTF-16
+        let lines: Vec<_> = paragraph
+            .get_line_metrics()
+            .iter()
+            .map(|line| {
+                byte_index(&self.text, line.start_index)..byte_index(&self.text, line.end_index)
+            })
+            .collect();
+
+        TextMetrics {
+            width: paragraph.max_width(),
+            height: paragraph.height(),
+            baseline: paragraph.alphabetic_baseline(),
+            line_count: lines.len(),
+            lines,
+        }
+    }
+
+    /// Shape the text and break it into lines that fit in `max_width`.
+    fn paragraph(&self, direction: TextDirection, max_width: f32) -> Paragraph {
+        let text_style = text_style(&self.style, self.color);
+
+        let mut paragraph_style = paragraph_style(
+            self.align.resolve(direction),
+            direction,
+            self.max_lines,
+            self.overflow,
+        );
+        paragraph_style.set_text_style(&text_style);
+
+        let mut builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+        builder.push_style(&text_style);
+        builder.add_text(&self.text);
+
+        let mut paragraph = builder.build();
+        layout_paragraph(&mut paragraph, max_width);
+        paragraph
+    }
 }
 
 pub struct TextBuilder {
     color: Color,
+    style: TextStyle,
     text: String,
-    align: Align,
+    align: TextAlign,
+    max_lines: Option<usize>,
+    overflow: TextOverflow,
+    selectable: bool,
 }
 
 impl TextBuilder {
     pub fn new(text: impl Into<String>) -> Self {
         Self {
             color: Color::BLACK,
+            style: TextStyle::default(),
             text: text.into(),
-            align: Align::Left,
+            align: TextAlign::Start,
+            max_lines: None,
+            overflow: TextOverflow::Clip,
+            selectable: false,
         }
     }
 
@@ -33,8 +123,9 @@ impl TextBuilder {
         Text::from(self)
     }
 
-    pub fn align(mut self, align: Align) -> Self {
-        self.align = align;
+    /// Defaults to `TextAlign::Start`, the start of the inherited text direction.
+    pub fn align(mut self, align: impl Into<TextAlign>) -> Self {
+        self.align = align.into();
         self
     }
 
@@ -42,25 +133,79 @@ impl TextBuilder {
         self.color = color;
         self
     }
+
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Let the text be selected with the mouse and copied.
+    pub fn selectable(mut self, selectable: bool) -> Self {
+        self.selectable = selectable;
+        self
+    }
 }
 
 impl From<TextBuilder> for Text {
     fn from(text_builder: TextBuilder) -> Self {
         Self {
             color: text_builder.color,
+            style: text_builder.style,
             text: text_builder.text,
             align: text_builder.align,
+            max_lines: text_builder.max_lines,
+            overflow: text_builder.overflow,
+            selection: text_builder
+                .selectable
+                .then(TextSelection::new),
         }
     }
 }
 
 impl Widget for Text {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match &mut self.selection {
+            Some(selection) => selection.send_event(event, || self.text.clone()),
+            None => false,
+        }
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        self.selection
+            .as_ref()
+            .is_some_and(|selection| selection.hit_test(position))
+    }
+
+    // Only takes the focus to copy the selection
+    fn focus_id(&self) -> Option<FocusId> {
+        self.selection
+            .as_ref()
+            .map(TextSelection::focus_id)
+    }
+
+    fn in_tab_order(&self) -> bool {
+        false
+    }
+
+    fn focus_ring(&self) -> Option<Rect> {
+        None
+    }
+
     fn get_size(&self, ctx: Context) -> (f32, f32) {
-        let size_char = (6.0, 12.5);
-        let mut width = (self.text.len() as f32) * size_char.0;
-        let height = size_char.1;
+        let paragraph = self.paragraph(ctx.direction, ctx.width);
+        let mut width = paragraph.max_width();
+        let height = paragraph.height();
 
-        // TODO(marc2332) break lines
         if width > ctx.width {
             width = ctx.width;
         }
@@ -68,18 +213,48 @@ impl Widget for Text {
         (width, height)
     }
 
-    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
-        let font = Font::default();
+    fn min_intrinsic_width(&self, ctx: Context) -> f32 {
+        // Longest word
+        self.paragraph(ctx.direction, ctx.width)
+            .min_intrinsic_width()
+    }
+
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        self.paragraph(ctx.direction, ctx.width)
+            .max_intrinsic_width()
+    }
 
-        let mut paint = Paint::default();
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.paragraph(ctx.direction, ctx.width)
+            .height()
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.paragraph(ctx.direction, ctx.width)
+            .height()
+    }
 
-        paint.set_anti_alias(true);
-        paint.set_style(PaintStyle::StrokeAndFill);
-        paint.set_color(self.color);
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        Some(
+            self.paragraph(ctx.direction, ctx.width)
+                .alphabetic_baseline(),
+        )
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let paragraph = self.paragraph(ctx.direction, ctx.width);
+        let x = align_x(
+            self.align.resolve(ctx.direction),
+            ctx.x,
+            ctx.width,
+            paragraph.max_width(),
+        );
 
-        let x = ctx.x;
-        let y = ctx.y + 9.0;
+        let paragraph = match &mut self.selection {
+            Some(selection) => selection.paint(canvas, paragraph, (x, ctx.y)),
+            None => &paragraph,
+        };
 
-        canvas.draw_str_align(&self.text, (x, y), &font, &paint, self.align);
+        paint_paragraph(canvas, paragraph, (x, ctx.y), ctx.height, self.overflow);
     }
 }
@@ -0,0 +1,1452 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::{Range, RangeInclusive},
+    time::{Duration, Instant},
+};
+
+use glutin::{
+    event::{
+        ElementState, Ime, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode,
+        WindowEvent,
+    },
+    window::CursorIcon,
+};
+pub use ropey::Rope;
+use skia_safe::{
+    textlayout::{Paragraph, ParagraphBuilder, RectHeightStyle, RectWidthStyle},
+    utils::text_utils::Align,
+    Canvas, Color, Paint, PaintStyle, Rect,
+};
+
+use super::text_input::{
+    is_shortcut, is_shortcut_text, is_word_jump, BLINK_INTERVAL, BORDER_COLOR,
+    FOCUSED_BORDER_COLOR, PADDING, SELECTION_COLOR,
+};
+use crate::{
+    clipboard, dispatch, rect_contains, request_redraw_at, set_ime_caret,
+    text_layout::{
+        add_composing_text, byte_index, caret_position, font_collection, next_grapheme, next_word,
+        paragraph_style, prev_grapheme, prev_word, text_style, utf16_index, visual_neighbor,
+        Preedit, TextOverflow, TextStyle,
+    },
+    Context, FocusId, KeyEvent, TextDirection, Widget, YalemEvent, YalemMouse,
+};
+
+/// Edits of the same kind made closer than this are undone together
+const UNDO_GROUP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Oldest edits are forgotten past this
+const MAX_UNDO: usize = 1000;
+
+/// Lines scrolled by one notch of the mouse wheel
+const WHEEL_LINES: f32 = 3.0;
+
+type ChangeCallback = Box<dyn FnMut(&Rope)>;
+type CursorCallback = Box<dyn FnMut(usize, usize)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// Undoable replacement of `removed` by `inserted`, at the char index `start`.
+struct Edit {
+    kind: EditKind,
+    start: usize,
+    removed: String,
+    inserted: String,
+    /// Caret and anchor before the edit, restored when it's undone
+    selection: (usize, usize),
+    time: Instant,
+}
+
+/// Height of each line once wrapped, in a treap ordered by line where each node also
+/// keeps the number of lines and the total height of its subtree. Finding the top of a
+/// line or the line at some height, and adding or removing lines, take O(log n) however
+/// long the text is.
+struct LineHeights {
+    root: Tree,
+    /// State of the generator of node priorities
+    seed: u32,
+}
+
+type Tree = Option<Box<HeightNode>>;
+
+struct HeightNode {
+    height: f32,
+    /// Higher than the priorities of the nodes below it
+    priority: u32,
+    /// Number of lines in the subtree
+    len: usize,
+    /// Total height of the lines in the subtree
+    total: f32,
+    left: Tree,
+    right: Tree,
+}
+
+impl HeightNode {
+    fn update(&mut self) {
+        self.len = 1 + tree_len(&self.left) + tree_len(&self.right);
+        self.total = self.height + tree_total(&self.left) + tree_total(&self.right);
+    }
+}
+
+fn tree_len(tree: &Tree) -> usize {
+    tree.as_ref().map_or(0, |node| node.len)
+}
+
+fn tree_total(tree: &Tree) -> f32 {
+    tree.as_ref()
+        .map_or(0.0, |node| node.total)
+}
+
+/// Split `tree` into its first `count` lines and the others.
+fn split(tree: Tree, count: usize) -> (Tree, Tree) {
+    let mut node = match tree {
+        Some(node) => node,
+        None => return (None, None),
+    };
+
+    let left_len = tree_len(&node.left);
+    if count <= left_len {
+        let (first, rest) = split(node.left.take(), count);
+        node.left = rest;
+        node.update();
+        (first, Some(node))
+    } else {
+        let (first, rest) = split(node.right.take(), count - left_len - 1);
+        node.right = first;
+        node.update();
+        (Some(node), rest)
+    }
+}
+
+/// The lines of `first` followed by the lines of `second`.
+fn merge(first: Tree, second: Tree) -> Tree {
+    match (first, second) {
+        (None, tree) | (tree, None) => tree,
+        (Some(mut first), Some(mut second)) => {
+            if first.priority > second.priority {
+                first.right = merge(first.right.take(), Some(second));
+                first.update();
+                Some(first)
+            } else {
+                second.left = merge(Some(first), second.left.take());
+                second.update();
+                Some(second)
+            }
+        }
+    }
+}
+
+/// Set the height of `line` under `node`, updating the totals on the way back up.
+fn set_height(node: &mut HeightNode, line: usize, height: f32) {
+    let left_len = tree_len(&node.left);
+    if line < left_len {
+        set_height(node.left.as_mut().unwrap(), line, height);
+    } else if line > left_len {
+        set_height(node.right.as_mut().unwrap(), line - left_len - 1, height);
+    } else {
+        node.height = height;
+    }
+    node.update();
+}
+
+impl LineHeights {
+    fn new(heights: Vec<f32>) -> Self {
+        let mut line_heights = Self {
+            root: None,
+            seed: 0x9E37_79B9,
+        };
+        line_heights.root = line_heights.build(heights);
+        line_heights
+    }
+
+    /// Tree of `heights`, in order.
+    fn build(&mut self, heights: Vec<f32>) -> Tree {
+        heights
+            .into_iter()
+            .fold(None, |tree, height| {
+                let node = HeightNode {
+                    height,
+                    priority: self.next_priority(),
+                    len: 1,
+                    total: height,
+                    left: None,
+                    right: None,
+                };
+                merge(tree, Some(Box::new(node)))
+            })
+    }
+
+    /// Pseudo-random priority, so the tree is balanced on average.
+    fn next_priority(&mut self) -> u32 {
+        // xorshift
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed
+    }
+
+    fn len(&self) -> usize {
+        tree_len(&self.root)
+    }
+
+    fn get(&self, line: usize) -> f32 {
+        let mut node = self.root.as_deref().unwrap();
+        let mut line = line;
+        loop {
+            let left_len = tree_len(&node.left);
+            if line < left_len {
+                node = node.left.as_deref().unwrap();
+            } else if line > left_len {
+                line -= left_len + 1;
+                node = node.right.as_deref().unwrap();
+            } else {
+                return node.height;
+            }
+        }
+    }
+
+    fn set(&mut self, line: usize, height: f32) {
+        if let Some(root) = &mut self.root {
+            set_height(root, line, height);
+        }
+    }
+
+    /// Sum of the heights of the lines before `line`.
+    fn top(&self, line: usize) -> f32 {
+        let mut node = self.root.as_deref();
+        let mut line = line;
+        let mut top = 0.0;
+        while let Some(current) = node {
+            let left_len = tree_len(&current.left);
+            if line <= left_len {
+                node = current.left.as_deref();
+            } else {
+                line -= left_len + 1;
+                top += tree_total(&current.left) + current.height;
+                node = current.right.as_deref();
+            }
+        }
+        top
+    }
+
+    fn total(&self) -> f32 {
+        tree_total(&self.root)
+    }
+
+    /// Line at the height `y`, and its top. Heights past the end are on the last line.
+    fn line_at(&self, y: f32) -> (usize, f32) {
+        let mut node = self.root.as_deref();
+        let mut line = 0;
+        let mut top = 0.0;
+        while let Some(current) = node {
+            let left_top = top + tree_total(&current.left);
+            let left_len = tree_len(&current.left);
+            if y < left_top {
+                node = current.left.as_deref();
+            } else if y < left_top + current.height {
+                return (line + left_len, left_top);
+            } else {
+                line += left_len + 1;
+                top = left_top + current.height;
+                node = current.right.as_deref();
+            }
+        }
+
+        let last = self.len() - 1;
+        (last, top - self.get(last))
+    }
+
+    /// Replace the heights of `lines` by `heights`.
+    fn splice(&mut self, lines: RangeInclusive<usize>, heights: impl Iterator<Item = f32>) {
+        let heights: Vec<f32> = heights.collect();
+
+        // Edits within the lines leave the tree as it is
+        if heights.len() == lines.clone().count() {
+            for (line, height) in lines.zip(heights) {
+                self.set(line, height);
+            }
+            return;
+        }
+
+        let (first, rest) = split(self.root.take(), *lines.start());
+        let (_, last) = split(rest, lines.count());
+        let middle = self.build(heights);
+        self.root = merge(merge(first, middle), last);
+    }
+}
+
+/// Whether `c` ends a line, the same way `Rope` splits lines.
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+/// Byte index in `text` of the char `index`.
+fn char_to_byte(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Multi-line editable text with soft wrapping, scrolling and undo.
+///
+/// The text is kept in a `Rope`, and only the lines in view are laid out,
+/// so large documents stay responsive.
+pub struct TextArea {
+    rope: Rope,
+    color: Color,
+    style: TextStyle,
+    on_change: Option<ChangeCallback>,
+    on_cursor_move: Option<CursorCallback>,
+    /// Char index of the caret
+    caret: usize,
+    /// Char index where the selection started, the caret is the other end
+    anchor: usize,
+    /// Horizontal position kept while moving up and down through shorter lines
+    goal_x: Option<f32>,
+    focus_id: FocusId,
+    accepts_tab: bool,
+    selecting: bool,
+    modifiers: ModifiersState,
+    preedit: Preedit,
+    last_change: Instant,
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+    /// Layouts of the lines in view, by line
+    paragraphs: HashMap<usize, Paragraph>,
+    heights: LineHeights,
+    /// Width lines are wrapped at
+    wrap_width: f32,
+    direction: TextDirection,
+    /// Height of a single line, used for lines that were never laid out
+    line_height: f32,
+    /// Distance from the top of the text to the top of the view
+    scroll: f32,
+    reveal_caret: bool,
+    area: Rect,
+    cursor: (f64, f64),
+}
+
+impl TextArea {
+    pub fn builder() -> TextAreaBuilder {
+        TextAreaBuilder::new()
+    }
+
+    fn selection(&self) -> (usize, usize) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    /// Content of `line`, without its line break.
+    fn line_text(&self, line: usize) -> String {
+        let mut text = self.rope.line(line).to_string();
+        if text.ends_with("\r\n") {
+            text.truncate(text.len() - 2);
+        } else if text.ends_with(is_line_break) {
+            text.pop();
+        }
+        text
+    }
+
+    /// Char index where the content of `line` ends, before its line break.
+    fn line_end(&self, line: usize) -> usize {
+        self.rope.line_to_char(line) + self.line_text(line).chars().count()
+    }
+
+    /// Text `line` is drawn with, including any preedit at the caret,
+    /// and the byte range of that preedit.
+    fn display_line(&self, line: usize) -> (String, Option<Range<usize>>) {
+        let text = self.line_text(line);
+        let caret_line = self.rope.char_to_line(self.caret);
+
+        if self.preedit.is_empty() || line != caret_line {
+            return (text, None);
+        }
+
+        let index = char_to_byte(&text, self.caret - self.rope.line_to_char(line));
+        let composing = index..index + self.preedit.text.len();
+        (self.preedit.compose(&text, index), Some(composing))
+    }
+
+    fn focused(&self) -> bool {
+        self.focus_id.is_focused()
+    }
+
+    /// Show `preedit` at the caret, in place of the previous one.
+    fn set_preedit(&mut self, preedit: Preedit) {
+        let line = self.rope.char_to_line(self.caret);
+        self.paragraphs.remove(&line);
+        self.preedit = preedit;
+        self.last_change = Instant::now();
+        self.reveal_caret = true;
+    }
+
+    fn paragraph(&self, text: &str, composing: Option<Range<usize>>) -> Paragraph {
+        let align = match self.direction {
+            TextDirection::Ltr => Align::Left,
+            TextDirection::Rtl => Align::Right,
+        };
+        let text_style = text_style(&self.style, self.color);
+
+        let mut paragraph_style = paragraph_style(align, self.direction, None, TextOverflow::Clip);
+        paragraph_style.set_text_style(&text_style);
+
+        let mut builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+        builder.push_style(&text_style);
+        add_composing_text(&mut builder, &text_style, text, composing);
+
+        let mut paragraph = builder.build();
+        paragraph.layout(self.wrap_width);
+        paragraph
+    }
+
+    /// Lay out `line` if it isn't already, and return its height.
+    fn layout_line(&mut self, line: usize) -> f32 {
+        if !self.paragraphs.contains_key(&line) {
+            let (text, composing) = self.display_line(line);
+            let paragraph = self.paragraph(&text, composing);
+            self.heights
+                .set(line, paragraph.height());
+            self.paragraphs.insert(line, paragraph);
+        }
+
+        self.heights.get(line)
+    }
+
+    /// Caret before the char `index`, as `(x, top, bottom)` in the text.
+    fn caret_position(&mut self, index: usize) -> (f32, f32, f32) {
+        let line = self.rope.char_to_line(index);
+        let (text, composing) = self.display_line(line);
+        let mut byte = char_to_byte(&text, index - self.rope.line_to_char(line));
+
+        // The caret moves inside the text being composed
+        if let Some(composing) = composing.filter(|_| index == self.caret) {
+            byte = composing.start
+                + self
+                    .preedit
+                    .cursor
+                    .unwrap_or(composing.len());
+        }
+
+        let top = self.heights.top(line);
+        self.layout_line(line);
+        let paragraph = &self.paragraphs[&line];
+
+        let (x, caret_top, caret_bottom) = caret_position(paragraph, &text, byte);
+        (x, top + caret_top, top + caret_bottom)
+    }
+
+    /// Char index closest to `(x, y)` in the text.
+    fn index_at(&mut self, (x, y): (f32, f32)) -> usize {
+        let (line, top) = self.heights.line_at(y.max(0.0));
+        let text = self.line_text(line);
+
+        self.layout_line(line);
+        let paragraph = &self.paragraphs[&line];
+        let position = paragraph.get_glyph_position_at_coordinate((x, y - top));
+        let byte = byte_index(&text, position.position.max(0) as usize);
+
+        self.rope.line_to_char(line) + text[..byte].chars().count()
+    }
+
+    /// Position in the text of a point in the window.
+    fn text_position(&self, (x, y): (f64, f64)) -> (f32, f32) {
+        (
+            x as f32 - self.area.left - PADDING,
+            y as f32 - self.area.top - PADDING + self.scroll,
+        )
+    }
+
+    /// Char index of the grapheme, or word, boundary before `index`.
+    fn prev_boundary(&self, index: usize, word: bool) -> usize {
+        let line = self.rope.char_to_line(index);
+        let line_start = self.rope.line_to_char(line);
+
+        // Step over the line break onto the previous line
+        if index == line_start {
+            return if line == 0 {
+                0
+            } else {
+                self.line_end(line - 1)
+            };
+        }
+
+        let text = self.line_text(line);
+        let byte = char_to_byte(&text, index - line_start);
+        let byte = if word {
+            prev_word(&text, byte)
+        } else {
+            prev_grapheme(&text, byte)
+        };
+
+        line_start + text[..byte].chars().count()
+    }
+
+    /// Char index of the grapheme, or word, boundary after `index`.
+    fn next_boundary(&self, index: usize, word: bool) -> usize {
+        let line = self.rope.char_to_line(index);
+
+        if index >= self.line_end(line) {
+            return if line + 1 < self.rope.len_lines() {
+                self.rope.line_to_char(line + 1)
+            } else {
+                index
+            };
+        }
+
+        let line_start = self.rope.line_to_char(line);
+        let text = self.line_text(line);
+        let byte = char_to_byte(&text, index - line_start);
+        let byte = if word {
+            next_word(&text, byte)
+        } else {
+            next_grapheme(&text, byte)
+        };
+
+        line_start + text[..byte].chars().count()
+    }
+
+    /// Char index of the caret position left, or right, of `index` on its line,
+    /// in visual order. `None` at the edge of the line.
+    fn visual_neighbor(&mut self, index: usize, right: bool) -> Option<usize> {
+        // The laid out line has the preedit in it
+        if !self.preedit.is_empty() {
+            return None;
+        }
+
+        let line = self.rope.char_to_line(index);
+        let line_start = self.rope.line_to_char(line);
+        let text = self.line_text(line);
+
+        self.layout_line(line);
+        let paragraph = self.paragraphs.get(&line)?;
+        let byte = visual_neighbor(
+            paragraph,
+            &text,
+            char_to_byte(&text, index - line_start),
+            right,
+        )?;
+
+        Some(line_start + text[..byte].chars().count())
+    }
+
+    /// Char index `distance` below the caret (above if negative), and the
+    /// horizontal position it was looked up at.
+    fn vertical_index(&mut self, distance: f32) -> (usize, f32) {
+        let (x, top, bottom) = self.caret_position(self.caret);
+        let x = self.goal_x.unwrap_or(x);
+        let y = (top + bottom) / 2.0 + distance;
+
+        let index = if y < 0.0 {
+            0
+        } else if y >= self.heights.total() {
+            self.rope.len_chars()
+        } else {
+            self.index_at((x, y))
+        };
+
+        (index, x)
+    }
+
+    fn move_caret(&mut self, caret: usize) {
+        self.caret = caret;
+        if !self.modifiers.shift() {
+            self.anchor = caret;
+        }
+        self.goal_x = None;
+        self.last_change = Instant::now();
+        self.reveal_caret = true;
+    }
+
+    /// Replace the chars in `start..end` by `text` without recording it for undo.
+    fn apply(&mut self, start: usize, end: usize, text: &str) {
+        let first_line = self.rope.char_to_line(start);
+        let last_line = self.rope.char_to_line(end);
+
+        self.rope.remove(start..end);
+        self.rope.insert(start, text);
+
+        // Lines after the edit only moved, the edited ones need a new layout
+        let inserted_end = start + text.chars().count();
+        let new_last_line = self.rope.char_to_line(inserted_end);
+        self.paragraphs = self
+            .paragraphs
+            .drain()
+            .filter_map(|(line, paragraph)| {
+                if line < first_line {
+                    Some((line, paragraph))
+                } else if line > last_line {
+                    Some((line - last_line + new_last_line, paragraph))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.heights.splice(
+            first_line..=last_line,
+            (first_line..=new_last_line).map(|_| self.line_height),
+        );
+
+        self.caret = inserted_end;
+        self.anchor = inserted_end;
+        self.goal_x = None;
+        self.last_change = Instant::now();
+        self.reveal_caret = true;
+
+        if let Some(on_change) = &mut self.on_change {
+            on_change(&self.rope);
+        }
+    }
+
+    /// Replace the chars in `start..end` by `text`, recording it for undo.
+    fn replace(&mut self, start: usize, end: usize, text: &str, kind: EditKind) {
+        if start == end && text.is_empty() {
+            return;
+        }
+
+        let removed = self.rope.slice(start..end).to_string();
+        let selection = (self.caret, self.anchor);
+        self.apply(start, end, text);
+        self.redo.clear();
+
+        let now = Instant::now();
+
+        // Merge with the previous edit when continuing to type or delete
+        if let Some(last) = self.undo.back_mut() {
+            if kind != EditKind::Other && last.kind == kind && now - last.time < UNDO_GROUP_INTERVAL
+            {
+                let last_end = last.start + last.inserted.chars().count();
+
+                if kind == EditKind::Typing && removed.is_empty() && start == last_end {
+                    last.inserted.push_str(text);
+                    last.time = now;
+                    return;
+                }
+
+                if kind == EditKind::Deleting && text.is_empty() && last.inserted.is_empty() {
+                    if end == last.start {
+                        last.removed.insert_str(0, &removed);
+                        last.start = start;
+                        last.time = now;
+                        return;
+                    }
+
+                    if start == last.start {
+                        last.removed.push_str(&removed);
+                        last.time = now;
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.undo.push_back(Edit {
+            kind,
+            start,
+            removed,
+            inserted: text.to_string(),
+            selection,
+            time: now,
+        });
+
+        if self.undo.len() > MAX_UNDO {
+            self.undo.pop_front();
+        }
+    }
+
+    fn insert(&mut self, text: &str, kind: EditKind) {
+        let (start, end) = self.selection();
+        self.replace(start, end, text, kind);
+    }
+
+    /// Delete the selection, or from the caret to `index` if there is none.
+    fn delete_to(&mut self, index: usize) {
+        let (start, end) = if self.has_selection() {
+            self.selection()
+        } else {
+            (self.caret.min(index), self.caret.max(index))
+        };
+
+        if start != end {
+            self.replace(start, end, "", EditKind::Deleting);
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(edit) = self.undo.pop_back() {
+            let end = edit.start + edit.inserted.chars().count();
+            self.apply(edit.start, end, &edit.removed);
+            (self.caret, self.anchor) = edit.selection;
+            self.redo.push(edit);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(mut edit) = self.redo.pop() {
+            let end = edit.start + edit.removed.chars().count();
+            self.apply(edit.start, end, &edit.inserted);
+            // Never merged with what's typed next
+            edit.kind = EditKind::Other;
+            self.undo.push_back(edit);
+        }
+    }
+
+    fn copy(&self) {
+        if self.has_selection() {
+            let (start, end) = self.selection();
+            clipboard::set_text(&self.rope.slice(start..end).to_string());
+        }
+    }
+
+    fn paste(&mut self) {
+        if let Some(text) = clipboard::get_text() {
+            let text: String = text
+                .replace("\r\n", "\n")
+                .chars()
+                .filter(|c| *c == '\n' || *c == '\t' || !c.is_control())
+                .collect();
+            self.insert(&text, EditKind::Other);
+        }
+    }
+
+    /// Returns whether the key does anything, the others are left to shortcuts.
+    fn on_key(&mut self, key: VirtualKeyCode) -> bool {
+        let word = is_word_jump(self.modifiers);
+        let shortcut = is_shortcut(self.modifiers);
+        let viewport = self.area.height() - PADDING * 2.0;
+
+        match key {
+            VirtualKeyCode::Left | VirtualKeyCode::Right => {
+                let right = key == VirtualKeyCode::Right;
+                // Going right goes back in right-to-left text
+                let forward = right == (self.direction == TextDirection::Ltr);
+
+                let caret = if self.has_selection() && !self.modifiers.shift() && !word {
+                    let (start, end) = self.selection();
+                    if forward {
+                        end
+                    } else {
+                        start
+                    }
+                } else if word && forward {
+                    self.next_boundary(self.caret, true)
+                } else if word {
+                    self.prev_boundary(self.caret, true)
+                } else {
+                    // Past the edge of the line, continue on the next or previous one
+                    self.visual_neighbor(self.caret, right)
+                        .unwrap_or_else(|| {
+                            if forward {
+                                self.next_boundary(self.caret, false)
+                            } else {
+                                self.prev_boundary(self.caret, false)
+                            }
+                        })
+                };
+                self.move_caret(caret);
+            }
+            VirtualKeyCode::Up | VirtualKeyCode::Down => {
+                let (_, top, bottom) = self.caret_position(self.caret);
+                let distance = bottom - top;
+                let distance = if key == VirtualKeyCode::Up {
+                    -distance
+                } else {
+                    distance
+                };
+
+                let (caret, x) = self.vertical_index(distance);
+                self.move_caret(caret);
+                self.goal_x = Some(x);
+            }
+            VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => {
+                let distance = if key == VirtualKeyCode::PageUp {
+                    -viewport
+                } else {
+                    viewport
+                };
+
+                let (caret, x) = self.vertical_index(distance);
+                self.scroll += distance;
+                self.move_caret(caret);
+                self.goal_x = Some(x);
+            }
+            VirtualKeyCode::Home if shortcut || self.modifiers.ctrl() => self.move_caret(0),
+            VirtualKeyCode::End if shortcut || self.modifiers.ctrl() => {
+                self.move_caret(self.rope.len_chars())
+            }
+            VirtualKeyCode::Home => {
+                let line = self.rope.char_to_line(self.caret);
+                self.move_caret(self.rope.line_to_char(line));
+            }
+            VirtualKeyCode::End => {
+                let line = self.rope.char_to_line(self.caret);
+                self.move_caret(self.line_end(line));
+            }
+            VirtualKeyCode::Back => {
+                let index = self.prev_boundary(self.caret, word);
+                self.delete_to(index);
+            }
+            VirtualKeyCode::Delete => {
+                let index = self.next_boundary(self.caret, word);
+                self.delete_to(index);
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                self.insert("\n", EditKind::Other);
+            }
+            VirtualKeyCode::Tab if self.accepts_tab => self.insert("\t", EditKind::Typing),
+            VirtualKeyCode::Z if shortcut && self.modifiers.shift() => self.redo(),
+            VirtualKeyCode::Z if shortcut => self.undo(),
+            VirtualKeyCode::Y if shortcut => self.redo(),
+            VirtualKeyCode::A if shortcut => {
+                self.anchor = 0;
+                self.caret = self.rope.len_chars();
+            }
+            VirtualKeyCode::C if shortcut => self.copy(),
+            VirtualKeyCode::X if shortcut => {
+                if self.has_selection() {
+                    self.copy();
+                    let (start, end) = self.selection();
+                    self.replace(start, end, "", EditKind::Other);
+                }
+            }
+            VirtualKeyCode::V if shortcut => self.paste(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Returns whether the event was handled.
+    fn handle_event(&mut self, event: &YalemEvent) -> bool {
+        match event {
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                button: MouseButton::Left,
+                position,
+                ..
+            }) => {
+                dispatch::take_focus(self.focus_id);
+                self.selecting = true;
+                if !self.preedit.is_empty() {
+                    self.set_preedit(Preedit::default());
+                }
+
+                let caret = self.index_at(self.text_position(*position));
+                self.move_caret(caret);
+                true
+            }
+            YalemEvent::YalemMouse(YalemMouse::Moved { position, .. }) => {
+                self.cursor = *position;
+
+                if self.selecting {
+                    self.caret = self.index_at(self.text_position(self.cursor));
+                    self.reveal_caret = true;
+                }
+                false
+            }
+            YalemEvent::YalemMouse(
+                YalemMouse::Released {
+                    button: MouseButton::Left,
+                    ..
+                }
+                | YalemMouse::Cancelled { .. },
+            ) => {
+                self.selecting = false;
+                false
+            }
+            YalemEvent::YalemMouse(YalemMouse::Wheel { delta, .. }) => {
+                self.scroll -= match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines * WHEEL_LINES * self.line_height,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                };
+                true
+            }
+            YalemEvent::Winit(WindowEvent::ModifiersChanged(modifiers)) => {
+                self.modifiers = *modifiers;
+                false
+            }
+            YalemEvent::Key(KeyEvent {
+                text: Some(text),
+                modifiers,
+                ..
+            }) if self.focused() => {
+                // Shortcuts are handled as key presses
+                if !is_shortcut_text(*modifiers) {
+                    self.insert(text, EditKind::Typing);
+                }
+                true
+            }
+            // Tab types a tab unless told otherwise, Shift+Tab is left to the window to
+            // move the focus back
+            YalemEvent::Key(KeyEvent {
+                key: Some(VirtualKeyCode::Tab),
+                modifiers,
+                ..
+            }) if modifiers.shift() || !self.accepts_tab => false,
+            YalemEvent::Winit(WindowEvent::Ime(ime)) if self.focused() => {
+                match ime {
+                    Ime::Preedit(text, cursor) => self.set_preedit(Preedit {
+                        text: text.clone(),
+                        cursor: cursor.map(|(_, end)| end),
+                    }),
+                    Ime::Commit(text) => {
+                        self.set_preedit(Preedit::default());
+                        self.insert(text, EditKind::Typing);
+                    }
+                    Ime::Disabled => self.set_preedit(Preedit::default()),
+                    Ime::Enabled => {}
+                }
+                true
+            }
+            YalemEvent::Key(KeyEvent {
+                key: Some(key),
+                state: ElementState::Pressed,
+                ..
+            }) if self.focused() => self.on_key(*key),
+            _ => false,
+        }
+    }
+}
+
+pub struct TextAreaBuilder {
+    value: String,
+    color: Color,
+    style: TextStyle,
+    on_change: Option<ChangeCallback>,
+    on_cursor_move: Option<CursorCallback>,
+    focus_id: Option<FocusId>,
+    accepts_tab: bool,
+}
+
+impl TextAreaBuilder {
+    pub fn new() -> Self {
+        Self {
+            value: String::new(),
+            color: Color::BLACK,
+            style: TextStyle::default(),
+            on_change: None,
+            on_cursor_move: None,
+            focus_id: None,
+            accepts_tab: true,
+        }
+    }
+
+    pub fn build(self) -> TextArea {
+        TextArea::from(self)
+    }
+
+    /// Initial text.
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// Id to move the focus to the text area with `request_focus`.
+    pub fn focus_id(mut self, id: FocusId) -> Self {
+        self.focus_id = Some(id);
+        self
+    }
+
+    /// Whether Tab types a tab, the default, or moves the focus to the next widget like
+    /// it does elsewhere.
+    pub fn accepts_tab(mut self, accepts_tab: bool) -> Self {
+        self.accepts_tab = accepts_tab;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Called with the whole text after every edit, undo and redo.
+    pub fn on_change<T>(mut self, callback: T) -> Self
+    where
+        T: FnMut(&Rope) + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Called with the line and column of the caret, both starting at 0, when it moves.
+    /// The column is counted in characters.
+    pub fn on_cursor_move<T>(mut self, callback: T) -> Self
+    where
+        T: FnMut(usize, usize) + 'static,
+    {
+        self.on_cursor_move = Some(Box::new(callback));
+        self
+    }
+}
+
+impl From<TextAreaBuilder> for TextArea {
+    fn from(text_area_builder: TextAreaBuilder) -> Self {
+        let rope = Rope::from_str(&text_area_builder.value);
+        let heights = LineHeights::new(vec![0.0; rope.len_lines()]);
+
+        Self {
+            rope,
+            color: text_area_builder.color,
+            style: text_area_builder.style,
+            on_change: text_area_builder.on_change,
+            on_cursor_move: text_area_builder.on_cursor_move,
+            caret: 0,
+            anchor: 0,
+            goal_x: None,
+            focus_id: text_area_builder
+                .focus_id
+                .unwrap_or_default(),
+            accepts_tab: text_area_builder.accepts_tab,
+            selecting: false,
+            modifiers: ModifiersState::empty(),
+            preedit: Preedit::default(),
+            last_change: Instant::now(),
+            undo: VecDeque::new(),
+            redo: vec![],
+            paragraphs: HashMap::new(),
+            heights,
+            wrap_width: 0.0,
+            direction: TextDirection::Ltr,
+            line_height: 0.0,
+            scroll: 0.0,
+            reveal_caret: false,
+            area: Rect::default(),
+            cursor: (0.0, 0.0),
+        }
+    }
+}
+
+impl Widget for TextArea {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        let caret = self.caret;
+        let handled = self.handle_event(event);
+
+        if self.caret != caret {
+            if let Some(on_cursor_move) = &mut self.on_cursor_move {
+                let line = self.rope.char_to_line(self.caret);
+                on_cursor_move(line, self.caret - self.rope.line_to_char(line));
+            }
+        }
+
+        handled
+    }
+
+    fn area(&self) -> Option<Rect> {
+        Some(self.area)
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        rect_contains(&self.area, position)
+    }
+
+    fn focus_id(&self) -> Option<FocusId> {
+        Some(self.focus_id)
+    }
+
+    // The border shows the focus
+    fn focus_ring(&self) -> Option<Rect> {
+        None
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Text)
+    }
+
+    fn accepts_text(&self) -> bool {
+        true
+    }
+
+    fn min_intrinsic_width(&self, _ctx: Context) -> f32 {
+        PADDING * 2.0
+    }
+
+    fn min_intrinsic_height(&self, _ctx: Context) -> f32 {
+        self.paragraph("", None).height() + PADDING * 2.0
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        self.area = Rect::from_xywh(ctx.x, ctx.y, ctx.width, ctx.height);
+
+        // Composition doesn't carry over when the focus moves elsewhere
+        if !self.focused() && !self.preedit.is_empty() {
+            self.set_preedit(Preedit::default());
+        }
+        let inner_width = (ctx.width - PADDING * 2.0).max(0.0);
+        let viewport = (ctx.height - PADDING * 2.0).max(0.0);
+
+        if inner_width != self.wrap_width || ctx.direction != self.direction {
+            // Every line wraps differently now
+            self.wrap_width = inner_width;
+            self.direction = ctx.direction;
+            self.line_height = self.paragraph("", None).height();
+            self.paragraphs.clear();
+            self.heights = LineHeights::new(vec![self.line_height; self.rope.len_lines()]);
+        }
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+        paint.set_color(Color::WHITE);
+        canvas.draw_rect(self.area, &paint);
+
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(1.0);
+        paint.set_color(if self.focused() {
+            FOCUSED_BORDER_COLOR
+        } else {
+            BORDER_COLOR
+        });
+        canvas.draw_rect(self.area, &paint);
+
+        // Scroll just enough to keep the caret visible
+        if self.reveal_caret {
+            self.reveal_caret = false;
+            let (_, top, bottom) = self.caret_position(self.caret);
+
+            if top < self.scroll {
+                self.scroll = top;
+            } else if bottom > self.scroll + viewport {
+                self.scroll = bottom - viewport;
+            }
+        }
+        self.scroll = self
+            .scroll
+            .min(self.heights.total() - viewport)
+            .max(0.0);
+
+        let text_x = ctx.x + PADDING;
+        let text_y = ctx.y + PADDING - self.scroll;
+
+        canvas.save();
+        canvas.clip_rect(
+            Rect::from_xywh(text_x, ctx.y + PADDING, inner_width, viewport),
+            None,
+            None,
+        );
+
+        let (selection_start, selection_end) = self.selection();
+        let mut selection_paint = Paint::default();
+        selection_paint.set_color(SELECTION_COLOR);
+
+        // Only the lines in view are laid out and painted
+        let (first, mut top) = self.heights.line_at(self.scroll);
+        let mut visible = first..first;
+        for line in first..self.heights.len() {
+            if top >= self.scroll + viewport {
+                break;
+            }
+            visible.end = line + 1;
+
+            let height = self.layout_line(line);
+            let paragraph = &self.paragraphs[&line];
+
+            let line_start = self.rope.line_to_char(line);
+            let line_end = self.line_end(line);
+            let start = selection_start.max(line_start);
+            let end = selection_end.min(line_end);
+
+            if self.focused() && start < end && self.preedit.is_empty() {
+                let text = self.line_text(line);
+                let range = utf16_index(&text, char_to_byte(&text, start - line_start))
+                    ..utf16_index(&text, char_to_byte(&text, end - line_start));
+
+                for text_box in paragraph.get_rects_for_range(
+                    range,
+                    RectHeightStyle::Max,
+                    RectWidthStyle::Tight,
+                ) {
+                    canvas.draw_rect(
+                        text_box
+                            .rect
+                            .with_offset((text_x, text_y + top)),
+                        &selection_paint,
+                    );
+                }
+            }
+
+            paragraph.paint(canvas, (text_x, text_y + top));
+            top += height;
+        }
+
+        // Forget the layouts of the lines out of view
+        self.paragraphs
+            .retain(|line, _| visible.contains(line));
+
+        if self.focused() {
+            let (x, top, bottom) = self.caret_position(self.caret);
+            set_ime_caret(Rect::new(
+                text_x + x,
+                text_y + top,
+                text_x + x + 1.0,
+                text_y + bottom,
+            ));
+
+            // Input methods hide the caret by giving no cursor in the preedit
+            let caret_visible = self.preedit.is_empty() || self.preedit.cursor.is_some();
+            let elapsed = self.last_change.elapsed().as_millis();
+            let interval = BLINK_INTERVAL.as_millis();
+
+            if caret_visible && (elapsed / interval).is_multiple_of(2) {
+                let mut paint = Paint::default();
+                paint.set_anti_alias(true);
+                paint.set_stroke_width(1.0);
+                paint.set_color(self.color);
+                canvas.draw_line(
+                    (text_x + x, text_y + top),
+                    (text_x + x, text_y + bottom),
+                    &paint,
+                );
+            }
+
+            let next_blink = (interval - elapsed % interval) as u64;
+            request_redraw_at(Instant::now() + Duration::from_millis(next_blink));
+        }
+
+        canvas.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_area(value: &str) -> TextArea {
+        let mut text_area = TextArea::builder().value(value).build();
+        text_area.caret = text_area.rope.len_chars();
+        text_area.anchor = text_area.caret;
+        text_area
+    }
+
+    fn text(text_area: &TextArea) -> String {
+        text_area.rope.to_string()
+    }
+
+    /// Make the last edit old enough not to be merged with the next one.
+    fn wait(text_area: &mut TextArea) {
+        let last = text_area.undo.back_mut().unwrap();
+        last.time -= UNDO_GROUP_INTERVAL;
+    }
+
+    #[test]
+    fn typing_is_undone_together() {
+        let mut text_area = text_area("");
+        for c in ["a", "b", "c"] {
+            text_area.insert(c, EditKind::Typing);
+        }
+
+        assert_eq!(text_area.undo.len(), 1);
+        text_area.undo();
+        assert_eq!(text(&text_area), "");
+        text_area.redo();
+        assert_eq!(text(&text_area), "abc");
+    }
+
+    #[test]
+    fn typing_after_a_pause_is_a_new_edit() {
+        let mut text_area = text_area("");
+        text_area.insert("a", EditKind::Typing);
+        wait(&mut text_area);
+        text_area.insert("b", EditKind::Typing);
+
+        assert_eq!(text_area.undo.len(), 2);
+        text_area.undo();
+        assert_eq!(text(&text_area), "a");
+    }
+
+    #[test]
+    fn typing_elsewhere_is_a_new_edit() {
+        let mut text_area = text_area("xy");
+        text_area.insert("a", EditKind::Typing);
+        text_area.caret = 0;
+        text_area.anchor = 0;
+        text_area.insert("b", EditKind::Typing);
+
+        assert_eq!(text(&text_area), "bxya");
+        assert_eq!(text_area.undo.len(), 2);
+        text_area.undo();
+        assert_eq!(text(&text_area), "xya");
+    }
+
+    #[test]
+    fn backspaces_are_undone_together() {
+        let mut text_area = text_area("abcd");
+        text_area.delete_to(3);
+        text_area.delete_to(2);
+        text_area.delete_to(1);
+
+        assert_eq!(text(&text_area), "a");
+        assert_eq!(text_area.undo.len(), 1);
+        text_area.undo();
+        assert_eq!(text(&text_area), "abcd");
+        assert_eq!(text_area.caret, 4);
+    }
+
+    #[test]
+    fn forward_deletes_are_undone_together() {
+        let mut text_area = text_area("abcd");
+        text_area.caret = 1;
+        text_area.anchor = 1;
+        text_area.delete_to(2);
+        text_area.delete_to(2);
+
+        assert_eq!(text(&text_area), "ad");
+        assert_eq!(text_area.undo.len(), 1);
+        text_area.undo();
+        assert_eq!(text(&text_area), "abcd");
+    }
+
+    #[test]
+    fn typing_and_deleting_are_separate_edits() {
+        let mut text_area = text_area("");
+        text_area.insert("ab", EditKind::Typing);
+        text_area.delete_to(1);
+        text_area.insert("c", EditKind::Typing);
+
+        assert_eq!(text_area.undo.len(), 3);
+        text_area.undo();
+        assert_eq!(text(&text_area), "a");
+        text_area.undo();
+        assert_eq!(text(&text_area), "ab");
+    }
+
+    #[test]
+    fn other_edits_are_never_merged() {
+        let mut text_area = text_area("");
+        text_area.insert("a", EditKind::Other);
+        text_area.insert("b", EditKind::Other);
+
+        assert_eq!(text_area.undo.len(), 2);
+    }
+
+    #[test]
+    fn redone_edits_are_not_merged_with_typing() {
+        let mut text_area = text_area("");
+        text_area.insert("a", EditKind::Typing);
+        text_area.undo();
+        text_area.redo();
+        text_area.insert("b", EditKind::Typing);
+
+        assert_eq!(text_area.undo.len(), 2);
+        text_area.undo();
+        assert_eq!(text(&text_area), "a");
+    }
+
+    #[test]
+    fn editing_clears_redo() {
+        let mut text_area = text_area("");
+        text_area.insert("a", EditKind::Typing);
+        text_area.undo();
+        text_area.insert("b", EditKind::Typing);
+        text_area.redo();
+
+        assert_eq!(text(&text_area), "b");
+    }
+
+    #[test]
+    fn oldest_edits_are_forgotten() {
+        let mut text_area = text_area("");
+        for _ in 0..MAX_UNDO + 10 {
+            text_area.insert("a", EditKind::Other);
+        }
+
+        assert_eq!(text_area.undo.len(), MAX_UNDO);
+        while !text_area.undo.is_empty() {
+            text_area.undo();
+        }
+        assert_eq!(text(&text_area), "a".repeat(10));
+    }
+
+    #[test]
+    fn line_heights() {
+        let mut heights = LineHeights::new(vec![10.0, 20.0, 30.0]);
+        assert_eq!(heights.top(0), 0.0);
+        assert_eq!(heights.top(2), 30.0);
+        assert_eq!(heights.total(), 60.0);
+
+        assert_eq!(heights.line_at(0.0), (0, 0.0));
+        assert_eq!(heights.line_at(9.9), (0, 0.0));
+        assert_eq!(heights.line_at(10.0), (1, 10.0));
+        assert_eq!(heights.line_at(45.0), (2, 30.0));
+        assert_eq!(heights.line_at(100.0), (2, 30.0));
+
+        heights.set(1, 5.0);
+        assert_eq!(heights.top(2), 15.0);
+        assert_eq!(heights.line_at(20.0), (2, 15.0));
+
+        heights.splice(1..=1, [1.0, 2.0, 3.0].into_iter());
+        assert_eq!(heights.len(), 5);
+        assert_eq!(heights.get(4), 30.0);
+        assert_eq!(heights.total(), 46.0);
+        assert_eq!(heights.line_at(13.0), (3, 13.0));
+    }
+
+    #[test]
+    fn line_heights_follow_edits_of_a_large_document() {
+        let mut expected: Vec<f32> = (0..50_000)
+            .map(|line| (line % 7 + 1) as f32)
+            .collect();
+        let mut heights = LineHeights::new(expected.clone());
+
+        let mut seed = 1u32;
+        let mut random = |below: usize| {
+            seed = seed
+                .wrapping_mul(1_103_515_245)
+                .wrapping_add(12345);
+            (seed >> 8) as usize % below
+        };
+        for _ in 0..2000 {
+            let start = random(expected.len());
+            let end = (start + random(4)).min(expected.len() - 1);
+            let new: Vec<f32> = (0..random(5) + 1)
+                .map(|_| (random(9) + 1) as f32)
+                .collect();
+
+            expected.splice(start..=end, new.iter().copied());
+            heights.splice(start..=end, new.into_iter());
+        }
+
+        assert_eq!(heights.len(), expected.len());
+        assert_eq!(heights.total(), expected.iter().sum::<f32>());
+        let mut top = 0.0;
+        for (line, height) in expected.iter().enumerate() {
+            if line % 101 == 0 {
+                assert_eq!(heights.get(line), *height);
+                assert_eq!(heights.top(line), top);
+                assert_eq!(heights.line_at(top), (line, top));
+                assert_eq!(heights.line_at(top + height / 2.0), (line, top));
+            }
+            top += height;
+        }
+    }
+
+    #[test]
+    fn edits_keep_a_height_per_line() {
+        let mut text_area = text_area(&"line\n".repeat(10_000));
+        text_area.caret = 12;
+        text_area.anchor = 12;
+        text_area.insert("a\nb\nc", EditKind::Typing);
+        assert_eq!(text_area.heights.len(), text_area.rope.len_lines());
+
+        text_area.delete_to(0);
+        assert_eq!(text_area.heights.len(), text_area.rope.len_lines());
+
+        text_area.undo();
+        text_area.undo();
+        assert_eq!(text_area.heights.len(), text_area.rope.len_lines());
+        assert_eq!(text(&text_area), "line\n".repeat(10_000));
+    }
+}
@@ -0,0 +1,950 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use glutin::{
+    event::{ElementState, Ime, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent},
+    window::CursorIcon,
+};
+use skia_safe::{
+    textlayout::{Paragraph, ParagraphBuilder, RectHeightStyle, RectWidthStyle},
+    utils::text_utils::Align,
+    Canvas, Color, Paint, PaintStyle, Rect,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    clipboard, dispatch, rect_contains, request_redraw_at, set_ime_caret,
+    text_layout::{
+        add_composing_text, align_x, byte_index, caret_position, font_collection, layout_paragraph,
+        next_grapheme, next_word, paragraph_style, prev_grapheme, prev_word, text_style,
+        utf16_index, visual_neighbor, word_at, Preedit, TextOverflow, TextStyle,
+    },
+    Context, FocusId, KeyEvent, TextDirection, Widget, YalemEvent, YalemMouse,
+};
+
+/// Space between the border and the text
+pub(crate) const PADDING: f32 = 4.0;
+
+/// How long the caret stays visible, and then hidden, when blinking
+pub(crate) const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Shown instead of every character in password mode
+const PASSWORD_CHAR: char = '\u{2022}';
+
+const PLACEHOLDER_COLOR: Color = Color::GRAY;
+pub(crate) const BORDER_COLOR: Color = Color::GRAY;
+pub(crate) const FOCUSED_BORDER_COLOR: Color = Color::BLUE;
+pub(crate) const SELECTION_COLOR: Color = Color::new(0x663399FF);
+
+type Callback = Box<dyn FnMut(&str)>;
+
+/// Modifier used for shortcuts like copy and paste.
+pub(crate) fn is_shortcut(modifiers: ModifiersState) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.logo()
+    } else {
+        modifiers.ctrl()
+    }
+}
+
+/// Modifier used to move the caret a word at a time.
+pub(crate) fn is_word_jump(modifiers: ModifiersState) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.alt()
+    } else {
+        modifiers.ctrl()
+    }
+}
+
+/// Whether text typed with `modifiers` held comes from a shortcut rather than the
+/// keyboard layout. AltGr is reported as Ctrl+Alt on Windows, text typed with it is
+/// kept.
+pub(crate) fn is_shortcut_text(modifiers: ModifiersState) -> bool {
+    modifiers.ctrl() && !modifiers.alt()
+}
+
+/// Single line editable text field.
+pub struct TextInput {
+    value: String,
+    placeholder: String,
+    password: bool,
+    color: Color,
+    style: TextStyle,
+    on_change: Option<Callback>,
+    on_submit: Option<Callback>,
+    /// Byte index of the caret in `value`
+    caret: usize,
+    /// Byte index where the selection started, the caret is the other end
+    anchor: usize,
+    focus_id: FocusId,
+    selecting: bool,
+    modifiers: ModifiersState,
+    preedit: Preedit,
+    /// Last time the caret moved, it doesn't blink right after that
+    last_change: Instant,
+    /// How far the text is scrolled to keep the caret visible
+    scroll: f32,
+    /// Direction the text was last drawn in
+    direction: TextDirection,
+    area: Rect,
+    text_x: f32,
+}
+
+impl TextInput {
+    pub fn builder() -> TextInputBuilder {
+        TextInputBuilder::new()
+    }
+
+    fn selection(&self) -> (usize, usize) {
+        (self.caret.min(self.anchor), self.caret.max(self.anchor))
+    }
+
+    fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    fn focused(&self) -> bool {
+        self.focus_id.is_focused()
+    }
+
+    /// What's actually drawn, i.e. the masked value in password mode.
+    fn display_text(&self) -> String {
+        if self.password {
+            self.value
+                .graphemes(true)
+                .map(|_| PASSWORD_CHAR)
+                .collect()
+        } else {
+            self.value.clone()
+        }
+    }
+
+    /// Byte index in the displayed text of the byte `index` in the value.
+    fn display_index(&self, index: usize) -> usize {
+        if self.password {
+            self.value[..index]
+                .graphemes(true)
+                .count()
+                * PASSWORD_CHAR.len_utf8()
+        } else {
+            index
+        }
+    }
+
+    /// Byte index in the value of the byte `index` in the displayed text.
+    fn value_index(&self, index: usize) -> usize {
+        if self.password {
+            self.value
+                .grapheme_indices(true)
+                .nth(index / PASSWORD_CHAR.len_utf8())
+                .map_or(self.value.len(), |(start, _)| start)
+        } else {
+            index
+        }
+    }
+
+    fn paragraph(
+        &self,
+        text: &str,
+        color: Color,
+        direction: TextDirection,
+        composing: Option<Range<usize>>,
+    ) -> Paragraph {
+        let text_style = text_style(&self.style, color);
+
+        let mut paragraph_style = paragraph_style(Align::Left, direction, None, TextOverflow::Clip);
+        paragraph_style.set_text_style(&text_style);
+
+        let mut builder = ParagraphBuilder::new(&paragraph_style, font_collection());
+        builder.push_style(&text_style);
+        add_composing_text(&mut builder, &text_style, text, composing);
+
+        let mut paragraph = builder.build();
+        // Never wrapped, it scrolls instead
+        layout_paragraph(&mut paragraph, 0.0);
+        paragraph
+    }
+
+    /// Byte index in the value closest to `position`.
+    fn index_at(&self, (x, y): (f64, f64)) -> usize {
+        let display = self.display_text();
+        let paragraph = self.paragraph(&display, self.color, self.direction, None);
+        let position = paragraph.get_glyph_position_at_coordinate((
+            x as f32 - self.text_x,
+            y as f32 - self.area.top - PADDING,
+        ));
+
+        self.value_index(byte_index(&display, position.position.max(0) as usize))
+    }
+
+    /// Byte index in the value of the caret position left, or right, of the caret,
+    /// in visual order.
+    fn visual_neighbor(&self, right: bool) -> Option<usize> {
+        let display = self.display_text();
+        let paragraph = self.paragraph(&display, self.color, self.direction, None);
+        let index = visual_neighbor(&paragraph, &display, self.display_index(self.caret), right)?;
+
+        Some(self.value_index(index))
+    }
+
+    fn move_caret(&mut self, caret: usize) {
+        self.caret = caret;
+        if !self.modifiers.shift() {
+            self.anchor = caret;
+        }
+        self.last_change = Instant::now();
+    }
+
+    /// Select the word at the byte `index`, or the whole value of a password, whose
+    /// words shouldn't show.
+    fn select_word(&mut self, index: usize) {
+        let word = if self.password {
+            0..self.value.len()
+        } else {
+            word_at(&self.value, index)
+        };
+        self.anchor = word.start;
+        self.caret = word.end;
+        self.last_change = Instant::now();
+    }
+
+    /// Replace the selection with `text`.
+    fn insert(&mut self, text: &str) {
+        // There is a single line
+        let text: String = text
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+
+        let (start, end) = self.selection();
+        // Deleting at either end of the value changes nothing
+        if start == end && text.is_empty() {
+            return;
+        }
+
+        self.value
+            .replace_range(start..end, &text);
+        self.caret = start + text.len();
+        self.anchor = self.caret;
+        self.changed();
+    }
+
+    /// Delete the selection, or from the caret to `index` if there is none.
+    fn delete_to(&mut self, index: usize) {
+        if !self.has_selection() {
+            self.anchor = index;
+        }
+
+        self.insert("");
+    }
+
+    fn changed(&mut self) {
+        self.last_change = Instant::now();
+        if let Some(on_change) = &mut self.on_change {
+            on_change(&self.value);
+        }
+    }
+
+    fn copy(&self) {
+        // Never leak a password
+        if self.has_selection() && !self.password {
+            let (start, end) = self.selection();
+            clipboard::set_text(&self.value[start..end]);
+        }
+    }
+
+    /// Returns whether the key does anything, the others are left to shortcuts.
+    fn on_key(&mut self, key: VirtualKeyCode) -> bool {
+        let word = is_word_jump(self.modifiers);
+
+        match key {
+            VirtualKeyCode::Left | VirtualKeyCode::Right => {
+                let right = key == VirtualKeyCode::Right;
+                // Going right goes back in right-to-left text
+                let forward = right == (self.direction == TextDirection::Ltr);
+
+                let caret = if word && forward {
+                    next_word(&self.value, self.caret)
+                } else if word {
+                    prev_word(&self.value, self.caret)
+                } else if self.has_selection() && !self.modifiers.shift() {
+                    let (start, end) = self.selection();
+                    if forward {
+                        end
+                    } else {
+                        start
+                    }
+                } else {
+                    self.visual_neighbor(right)
+                        .unwrap_or(self.caret)
+                };
+                self.move_caret(caret);
+            }
+            VirtualKeyCode::Home => self.move_caret(0),
+            VirtualKeyCode::End => self.move_caret(self.value.len()),
+            VirtualKeyCode::Back => {
+                let index = if word {
+                    prev_word(&self.value, self.caret)
+                } else {
+                    prev_grapheme(&self.value, self.caret)
+                };
+                self.delete_to(index);
+            }
+            VirtualKeyCode::Delete => {
+                let index = if word {
+                    next_word(&self.value, self.caret)
+                } else {
+                    next_grapheme(&self.value, self.caret)
+                };
+                self.delete_to(index);
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                if let Some(on_submit) = &mut self.on_submit {
+                    on_submit(&self.value);
+                }
+            }
+            VirtualKeyCode::A if is_shortcut(self.modifiers) => {
+                self.anchor = 0;
+                self.caret = self.value.len();
+            }
+            VirtualKeyCode::C if is_shortcut(self.modifiers) => self.copy(),
+            VirtualKeyCode::X if is_shortcut(self.modifiers) => {
+                if !self.password {
+                    self.copy();
+                    self.insert("");
+                }
+            }
+            VirtualKeyCode::V if is_shortcut(self.modifiers) => {
+                if let Some(text) = clipboard::get_text() {
+                    self.insert(&text);
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+pub struct TextInputBuilder {
+    value: String,
+    placeholder: String,
+    password: bool,
+    color: Color,
+    style: TextStyle,
+    on_change: Option<Callback>,
+    on_submit: Option<Callback>,
+    focus_id: Option<FocusId>,
+}
+
+impl TextInputBuilder {
+    pub fn new() -> Self {
+        Self {
+            value: String::new(),
+            placeholder: String::new(),
+            password: false,
+            color: Color::BLACK,
+            style: TextStyle::default(),
+            on_change: None,
+            on_submit: None,
+            focus_id: None,
+        }
+    }
+
+    pub fn build(self) -> TextInput {
+        TextInput::from(self)
+    }
+
+    /// Initial text.
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// Shown while the input is empty.
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Id to move the focus to the input with `request_focus`.
+    pub fn focus_id(mut self, id: FocusId) -> Self {
+        self.focus_id = Some(id);
+        self
+    }
+
+    /// Mask the text, and don't let it be copied.
+    pub fn password(mut self, password: bool) -> Self {
+        self.password = password;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Called with the new text after every edit.
+    pub fn on_change<T>(mut self, callback: T) -> Self
+    where
+        T: FnMut(&str) + 'static,
+    {
+        self.on_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Called with the text when Enter is pressed.
+    pub fn on_submit<T>(mut self, callback: T) -> Self
+    where
+        T: FnMut(&str) + 'static,
+    {
+        self.on_submit = Some(Box::new(callback));
+        self
+    }
+}
+
+impl From<TextInputBuilder> for TextInput {
+    fn from(text_input_builder: TextInputBuilder) -> Self {
+        let caret = text_input_builder.value.len();
+
+        Self {
+            value: text_input_builder.value,
+            placeholder: text_input_builder.placeholder,
+            password: text_input_builder.password,
+            color: text_input_builder.color,
+            style: text_input_builder.style,
+            on_change: text_input_builder.on_change,
+            on_submit: text_input_builder.on_submit,
+            caret,
+            anchor: caret,
+            focus_id: text_input_builder
+                .focus_id
+                .unwrap_or_default(),
+            selecting: false,
+            modifiers: ModifiersState::empty(),
+            preedit: Preedit::default(),
+            last_change: Instant::now(),
+            scroll: 0.0,
+            direction: TextDirection::Ltr,
+            area: Rect::default(),
+            text_x: 0.0,
+        }
+    }
+}
+
+impl Widget for TextInput {
+    fn send_event(&mut self, event: &YalemEvent) -> bool {
+        match event {
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                button: MouseButton::Left,
+                position,
+                click_count,
+                ..
+            }) => {
+                dispatch::take_focus(self.focus_id);
+                self.preedit = Preedit::default();
+
+                let index = self.index_at(*position);
+                if *click_count == 2 {
+                    self.select_word(index);
+                } else {
+                    self.selecting = true;
+                    self.move_caret(index);
+                }
+                true
+            }
+            YalemEvent::YalemMouse(YalemMouse::Moved { position, .. }) if self.selecting => {
+                self.caret = self.index_at(*position);
+                false
+            }
+            YalemEvent::YalemMouse(
+                YalemMouse::Released {
+                    button: MouseButton::Left,
+                    ..
+                }
+                | YalemMouse::Cancelled { .. },
+            ) => {
+                self.selecting = false;
+                false
+            }
+            YalemEvent::Winit(WindowEvent::ModifiersChanged(modifiers)) => {
+                self.modifiers = *modifiers;
+                false
+            }
+            YalemEvent::Key(KeyEvent {
+                text: Some(text),
+                modifiers,
+                ..
+            }) if self.focused() => {
+                // Shortcuts are handled as key presses
+                if !is_shortcut_text(*modifiers) {
+                    self.insert(text);
+                }
+                true
+            }
+            // Left to the window, to move the focus
+            YalemEvent::Key(KeyEvent {
+                key: Some(VirtualKeyCode::Tab),
+                ..
+            }) => false,
+            // Input methods are kept disabled for passwords, see `draw`
+            YalemEvent::Winit(WindowEvent::Ime(ime)) if self.focused() && !self.password => {
+                match ime {
+                    Ime::Preedit(text, cursor) => {
+                        self.preedit = Preedit {
+                            text: text.clone(),
+                            cursor: cursor.map(|(_, end)| end),
+                        };
+                        self.last_change = Instant::now();
+                    }
+                    Ime::Commit(text) => {
+                        self.preedit = Preedit::default();
+                        self.insert(text);
+                    }
+                    Ime::Disabled => self.preedit = Preedit::default(),
+                    Ime::Enabled => {}
+                }
+                true
+            }
+            YalemEvent::Key(KeyEvent {
+                key: Some(key),
+                state: ElementState::Pressed,
+                ..
+            }) if self.focused() => self.on_key(*key),
+            _ => false,
+        }
+    }
+
+    fn area(&self) -> Option<Rect> {
+        Some(self.area)
+    }
+
+    fn hit_test(&self, position: (f64, f64)) -> bool {
+        rect_contains(&self.area, position)
+    }
+
+    fn focus_id(&self) -> Option<FocusId> {
+        Some(self.focus_id)
+    }
+
+    // The border shows the focus
+    fn focus_ring(&self) -> Option<Rect> {
+        None
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Text)
+    }
+
+    fn accepts_text(&self) -> bool {
+        true
+    }
+
+    fn get_size(&self, ctx: Context) -> (f32, f32) {
+        let paragraph = self.paragraph(&self.placeholder, PLACEHOLDER_COLOR, ctx.direction, None);
+        (ctx.width, paragraph.height() + PADDING * 2.0)
+    }
+
+    fn min_intrinsic_width(&self, _ctx: Context) -> f32 {
+        PADDING * 2.0
+    }
+
+    // Wide enough for the value, or the placeholder when it's empty
+    fn max_intrinsic_width(&self, ctx: Context) -> f32 {
+        let display = self.display_text();
+        let paragraph = if display.is_empty() {
+            self.paragraph(&self.placeholder, PLACEHOLDER_COLOR, ctx.direction, None)
+        } else {
+            self.paragraph(&display, self.color, ctx.direction, None)
+        };
+        paragraph.max_intrinsic_width().ceil() + PADDING * 2.0
+    }
+
+    fn min_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.get_size(ctx).1
+    }
+
+    fn max_intrinsic_height(&self, ctx: Context) -> f32 {
+        self.get_size(ctx).1
+    }
+
+    fn baseline(&self, ctx: Context) -> Option<f32> {
+        let paragraph = self.paragraph(&self.placeholder, PLACEHOLDER_COLOR, ctx.direction, None);
+        Some(paragraph.alphabetic_baseline() + PADDING)
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: Context) {
+        let (width, height) = self.get_size(ctx.clone());
+        self.area = Rect::from_xywh(ctx.x, ctx.y, width, height);
+        self.direction = ctx.direction;
+
+        // Composition doesn't carry over when the focus moves elsewhere
+        if !self.focused() {
+            self.preedit = Preedit::default();
+        }
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+        paint.set_color(Color::WHITE);
+        canvas.draw_rect(self.area, &paint);
+
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(1.0);
+        paint.set_color(if self.focused() {
+            FOCUSED_BORDER_COLOR
+        } else {
+            BORDER_COLOR
+        });
+        canvas.draw_rect(self.area, &paint);
+
+        let mut display = self.display_text();
+        let mut caret_index = self.display_index(self.caret);
+        let mut composing = None;
+
+        // Text being composed is shown at the caret, until it's committed
+        if !self.preedit.is_empty() {
+            display = self
+                .preedit
+                .compose(&display, caret_index);
+            composing = Some(caret_index..caret_index + self.preedit.text.len());
+            caret_index += self
+                .preedit
+                .cursor
+                .unwrap_or(self.preedit.text.len());
+        }
+
+        let paragraph = if display.is_empty() {
+            self.paragraph(&self.placeholder, PLACEHOLDER_COLOR, ctx.direction, None)
+        } else {
+            self.paragraph(&display, self.color, ctx.direction, composing)
+        };
+
+        let inner_x = ctx.x + PADDING;
+        let inner_width = width - PADDING * 2.0;
+        let text_y = ctx.y + PADDING;
+        let align = match ctx.direction {
+            TextDirection::Ltr => Align::Left,
+            TextDirection::Rtl => Align::Right,
+        };
+
+        let caret = if display.is_empty() {
+            0.0
+        } else {
+            caret_position(&paragraph, &display, caret_index).0
+        };
+
+        // Scroll just enough to keep the caret visible
+        if caret - self.scroll > inner_width {
+            self.scroll = caret - inner_width;
+        } else if caret < self.scroll {
+            self.scroll = caret;
+        }
+        self.scroll = self
+            .scroll
+            .min((paragraph.max_width() - inner_width).max(0.0));
+
+        self.text_x = if paragraph.max_width() <= inner_width {
+            align_x(align, inner_x, inner_width, paragraph.max_width())
+        } else {
+            inner_x - self.scroll
+        };
+
+        let caret = if display.is_empty() {
+            align_x(align, inner_x, inner_width, 0.0)
+        } else {
+            self.text_x + caret
+        };
+
+        canvas.save();
+        canvas.clip_rect(
+            Rect::from_xywh(inner_x, ctx.y, inner_width, height),
+            None,
+            None,
+        );
+
+        if self.focused() && self.has_selection() && self.preedit.is_empty() {
+            let (start, end) = self.selection();
+            let range = utf16_index(&display, self.display_index(start))
+                ..utf16_index(&display, self.display_index(end));
+
+            let mut paint = Paint::default();
+            paint.set_color(SELECTION_COLOR);
+
+            for text_box in
+                paragraph.get_rects_for_range(range, RectHeightStyle::Max, RectWidthStyle::Tight)
+            {
+                canvas.draw_rect(
+                    text_box
+                        .rect
+                        .with_offset((self.text_x, text_y)),
+                    &paint,
+                );
+            }
+        }
+
+        paragraph.paint(canvas, (self.text_x, text_y));
+
+        // Input methods hide the caret by giving no cursor in the preedit
+        let caret_visible = self.preedit.is_empty() || self.preedit.cursor.is_some();
+
+        if self.focused() && !self.password {
+            set_ime_caret(Rect::from_xywh(caret, text_y, 1.0, paragraph.height()));
+        }
+
+        if self.focused() && caret_visible {
+            let elapsed = self.last_change.elapsed().as_millis();
+            let interval = BLINK_INTERVAL.as_millis();
+
+            if (elapsed / interval).is_multiple_of(2) {
+                let mut paint = Paint::default();
+                paint.set_anti_alias(true);
+                paint.set_stroke_width(1.0);
+                paint.set_color(self.color);
+                canvas.draw_line(
+                    (caret, text_y),
+                    (caret, text_y + paragraph.height()),
+                    &paint,
+                );
+            }
+
+            let next_blink = (interval - elapsed % interval) as u64;
+            request_redraw_at(Instant::now() + Duration::from_millis(next_blink));
+        }
+
+        canvas.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_focus;
+
+    fn text_input(value: &str) -> TextInput {
+        TextInput::builder()
+            .value(value)
+            .build()
+    }
+
+    fn shortcut() -> ModifiersState {
+        if cfg!(target_os = "macos") {
+            ModifiersState::LOGO
+        } else {
+            ModifiersState::CTRL
+        }
+    }
+
+    fn word_jump() -> ModifiersState {
+        if cfg!(target_os = "macos") {
+            ModifiersState::ALT
+        } else {
+            ModifiersState::CTRL
+        }
+    }
+
+    fn press(text_input: &mut TextInput, key: VirtualKeyCode, modifiers: ModifiersState) {
+        text_input.modifiers = modifiers;
+        text_input.on_key(key);
+        text_input.modifiers = ModifiersState::empty();
+    }
+
+    fn type_text(text_input: &mut TextInput, text: &str, modifiers: ModifiersState) -> bool {
+        text_input.send_event(&YalemEvent::Key(KeyEvent {
+            key: None,
+            state: ElementState::Pressed,
+            text: Some(text.to_string()),
+            modifiers,
+            repeat: false,
+        }))
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut text_input = text_input("hello world");
+        text_input.anchor = 0;
+        text_input.caret = 5;
+        text_input.insert("bye");
+
+        assert_eq!(text_input.value, "bye world");
+        assert_eq!((text_input.anchor, text_input.caret), (3, 3));
+    }
+
+    #[test]
+    fn shift_extends_the_selection() {
+        let mut text_input = text_input("hello");
+        press(&mut text_input, VirtualKeyCode::Home, ModifiersState::SHIFT);
+
+        assert_eq!(text_input.selection(), (0, 5));
+        press(
+            &mut text_input,
+            VirtualKeyCode::End,
+            ModifiersState::empty(),
+        );
+        assert!(!text_input.has_selection());
+    }
+
+    #[test]
+    fn select_all() {
+        let mut text_input = text_input("hello");
+        press(&mut text_input, VirtualKeyCode::A, shortcut());
+
+        assert_eq!(text_input.selection(), (0, 5));
+    }
+
+    #[test]
+    fn word_jumps() {
+        let mut text_input = text_input("one two three");
+        press(&mut text_input, VirtualKeyCode::Left, word_jump());
+        assert_eq!(text_input.caret, 8);
+        press(&mut text_input, VirtualKeyCode::Left, word_jump());
+        assert_eq!(text_input.caret, 4);
+        press(&mut text_input, VirtualKeyCode::Right, word_jump());
+        assert_eq!(text_input.caret, 7);
+    }
+
+    #[test]
+    fn word_deletes() {
+        let mut text_input = text_input("one two three");
+        press(&mut text_input, VirtualKeyCode::Back, word_jump());
+        assert_eq!(text_input.value, "one two ");
+
+        press(
+            &mut text_input,
+            VirtualKeyCode::Home,
+            ModifiersState::empty(),
+        );
+        press(&mut text_input, VirtualKeyCode::Delete, word_jump());
+        assert_eq!(text_input.value, " two ");
+    }
+
+    #[test]
+    fn deletes_whole_graphemes() {
+        let mut text_input = text_input("ae\u{301}");
+        press(
+            &mut text_input,
+            VirtualKeyCode::Back,
+            ModifiersState::empty(),
+        );
+
+        assert_eq!(text_input.value, "a");
+    }
+
+    #[test]
+    fn deleting_at_the_edges_changes_nothing() {
+        let changes = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = changes.clone();
+        let mut text_input = TextInput::builder()
+            .value("a")
+            .on_change(move |_| counter.set(counter.get() + 1))
+            .build();
+
+        press(
+            &mut text_input,
+            VirtualKeyCode::Delete,
+            ModifiersState::empty(),
+        );
+        press(
+            &mut text_input,
+            VirtualKeyCode::Home,
+            ModifiersState::empty(),
+        );
+        press(
+            &mut text_input,
+            VirtualKeyCode::Back,
+            ModifiersState::empty(),
+        );
+        assert_eq!(changes.get(), 0);
+    }
+
+    #[test]
+    fn double_click_selects_a_word() {
+        let mut text_input = text_input("hello wide world");
+        text_input.select_word(8);
+        assert_eq!(text_input.selection(), (6, 10));
+
+        let mut password = TextInput::builder()
+            .value("hello world")
+            .password(true)
+            .build();
+        password.select_word(2);
+        assert_eq!(password.selection(), (0, 11));
+    }
+
+    #[test]
+    fn cut_copy_and_paste() {
+        let mut text_input = text_input("hello world");
+        text_input.anchor = 6;
+        press(&mut text_input, VirtualKeyCode::X, shortcut());
+        assert_eq!(text_input.value, "hello ");
+
+        press(
+            &mut text_input,
+            VirtualKeyCode::Home,
+            ModifiersState::empty(),
+        );
+        press(&mut text_input, VirtualKeyCode::V, shortcut());
+        assert_eq!(text_input.value, "worldhello ");
+
+        press(&mut text_input, VirtualKeyCode::A, shortcut());
+        press(&mut text_input, VirtualKeyCode::C, shortcut());
+        assert_eq!(clipboard::get_text().as_deref(), Some("worldhello "));
+    }
+
+    #[test]
+    fn pasted_line_breaks_are_dropped() {
+        let mut text_input = text_input("");
+        clipboard::set_text("one\ntwo");
+        press(&mut text_input, VirtualKeyCode::V, shortcut());
+
+        assert_eq!(text_input.value, "onetwo");
+    }
+
+    #[test]
+    fn passwords_are_never_copied() {
+        let mut text_input = TextInput::builder()
+            .value("secret")
+            .password(true)
+            .build();
+        clipboard::set_text("clipboard");
+        press(&mut text_input, VirtualKeyCode::A, shortcut());
+        press(&mut text_input, VirtualKeyCode::C, shortcut());
+        press(&mut text_input, VirtualKeyCode::X, shortcut());
+
+        assert_eq!(clipboard::get_text().as_deref(), Some("clipboard"));
+        assert_eq!(text_input.value, "secret");
+    }
+
+    #[test]
+    fn altgr_text_is_typed() {
+        let mut text_input = text_input("");
+        set_focus(Some(text_input.focus_id));
+
+        assert!(type_text(
+            &mut text_input,
+            "@",
+            ModifiersState::CTRL | ModifiersState::ALT
+        ));
+        assert_eq!(text_input.value, "@");
+
+        // Ctrl alone makes it a shortcut
+        assert!(type_text(&mut text_input, "v", ModifiersState::CTRL));
+        assert_eq!(text_input.value, "@");
+    }
+
+    #[test]
+    fn text_is_only_typed_with_the_focus() {
+        let mut text_input = text_input("");
+        set_focus(None);
+
+        assert!(!type_text(&mut text_input, "a", ModifiersState::empty()));
+        assert_eq!(text_input.value, "");
+    }
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use glutin::event::{ElementState, MouseButton, VirtualKeyCode};
+use skia_safe::{
+    textlayout::{Paragraph, RectHeightStyle, RectWidthStyle},
+    Canvas, Paint, Rect,
+};
+
+use super::text_input::{is_shortcut, SELECTION_COLOR};
+use crate::{
+    clipboard, dispatch, rect_contains, text_layout::byte_index, FocusId, KeyEvent, YalemEvent,
+    YalemMouse,
+};
+
+/// Character Skia puts in the paragraph text for each inline widget
+const PLACEHOLDER_CHAR: char = '\u{FFFC}';
+
+/// Selection of read-only text, for `Text` and `RichText`.
+///
+/// Indices are in UTF-16 code units, like the paragraph's.
+pub(crate) struct TextSelection {
+    anchor: usize,
+    caret: usize,
+    selecting: bool,
+    focus_id: FocusId,
+    /// Paragraph as last drawn, and where
+    paragraph: Option<Paragraph>,
+    origin: (f32, f32),
+}
+
+impl TextSelection {
+    pub(crate) fn new() -> Self {
+        Self {
+            anchor: 0,
+            caret: 0,
+            selecting: false,
+            focus_id: FocusId::new(),
+            paragraph: None,
+            origin: (0.0, 0.0),
+        }
+    }
+
+    fn range(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    pub(crate) fn focus_id(&self) -> FocusId {
+        self.focus_id
+    }
+
+    /// Area of the paragraph as last drawn.
+    pub(crate) fn area(&self) -> Option<Rect> {
+        let paragraph = self.paragraph.as_ref()?;
+        let (x, y) = self.origin;
+        Some(Rect::from_xywh(
+            x,
+            y,
+            paragraph.max_width(),
+            paragraph.height(),
+        ))
+    }
+
+    pub(crate) fn hit_test(&self, position: (f64, f64)) -> bool {
+        self.area()
+            .is_some_and(|area| rect_contains(&area, position))
+    }
+
+    /// UTF-16 index closest to `position` in the window.
+    fn index_at(&self, (x, y): (f64, f64)) -> Option<usize> {
+        let paragraph = self.paragraph.as_ref()?;
+        let position = paragraph
+            .get_glyph_position_at_coordinate((x as f32 - self.origin.0, y as f32 - self.origin.1));
+        Some(position.position.max(0) as usize)
+    }
+
+    /// Handle selection events and return whether the event was handled. `text` gives
+    /// the text of the paragraph, it's only called when copying.
+    pub(crate) fn send_event(&mut self, event: &YalemEvent, text: impl FnOnce() -> String) -> bool {
+        match event {
+            YalemEvent::YalemMouse(YalemMouse::Pressed {
+                button: MouseButton::Left,
+                position,
+                modifiers,
+                click_count,
+                ..
+            }) => {
+                let index = match self.index_at(*position) {
+                    Some(index) => index,
+                    None => return false,
+                };
+                dispatch::take_focus(self.focus_id);
+
+                if *click_count == 2 {
+                    let word = self
+                        .paragraph
+                        .as_ref()
+                        .unwrap()
+                        .get_word_boundary(index as u32);
+                    self.anchor = word.start;
+                    self.caret = word.end;
+                    self.selecting = false;
+                } else {
+                    self.caret = index;
+                    if !modifiers.shift() {
+                        self.anchor = index;
+                    }
+                    self.selecting = true;
+                }
+                true
+            }
+            YalemEvent::YalemMouse(YalemMouse::Moved { position, .. }) if self.selecting => {
+                if let Some(index) = self.index_at(*position) {
+                    self.caret = index;
+                }
+                false
+            }
+            YalemEvent::YalemMouse(
+                YalemMouse::Released {
+                    button: MouseButton::Left,
+                    ..
+                }
+                | YalemMouse::Cancelled { .. },
+            ) => {
+                self.selecting = false;
+                false
+            }
+            YalemEvent::Key(KeyEvent {
+                key: Some(VirtualKeyCode::C),
+                state: ElementState::Pressed,
+                modifiers,
+                ..
+            }) if self.focus_id.is_focused()
+                && is_shortcut(*modifiers)
+                && !self.range().is_empty() =>
+            {
+                let range = self.range();
+                let text = text();
+                let selected: String = text
+                    [byte_index(&text, range.start)..byte_index(&text, range.end)]
+                    .chars()
+                    .filter(|c| *c != PLACEHOLDER_CHAR)
+                    .collect();
+                clipboard::set_text(&selected);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Paint the highlight behind the selected glyphs of `paragraph`, drawn at `origin`,
+    /// and keep the paragraph for hit testing. Returns it so the text is painted on top.
+    pub(crate) fn paint(
+        &mut self,
+        canvas: &mut Canvas,
+        paragraph: Paragraph,
+        origin: (f32, f32),
+    ) -> &Paragraph {
+        // Only the focused text keeps its selection
+        if !self.focus_id.is_focused() {
+            self.anchor = self.caret;
+            self.selecting = false;
+        }
+
+        let range = self.range();
+
+        if !range.is_empty() {
+            let mut paint = Paint::default();
+            paint.set_color(SELECTION_COLOR);
+
+            for text_box in
+                paragraph.get_rects_for_range(range, RectHeightStyle::Max, RectWidthStyle::Tight)
+            {
+                canvas.draw_rect(text_box.rect.with_offset(origin), &paint);
+            }
+        }
+
+        self.origin = origin;
+        self.paragraph.insert(paragraph)
+    }
+}